@@ -0,0 +1,244 @@
+use crate::{
+    config::sweep::SplitMix64,
+    database::{export::ExportRow, RunStatus, Satisfiability, Verification},
+};
+use std::fmt::Write;
+
+pub mod check;
+#[cfg(test)]
+mod check_test;
+pub mod compare;
+#[cfg(test)]
+mod compare_test;
+pub mod report;
+#[cfg(test)]
+mod report_test;
+
+/// Whether `row` gave a verifiable answer within the timeout of its test set
+pub fn solved(row: &ExportRow) -> bool {
+    row.status == RunStatus::Ok.kind() as i32
+        && row.satisfiable != Satisfiability::Unknown as i32
+        && row.verification != Verification::Invalid as i32
+        && row.runtime.map_or(false, |runtime| runtime <= row.timeout)
+}
+
+/// Runtime of `row` in ms, unsolved runs are penalized with `factor` times the timeout
+pub fn penalized_runtime(row: &ExportRow, factor: u32) -> f64 {
+    match row.runtime {
+        Some(runtime) if solved(row) => runtime as f64,
+        _ => (factor as i64 * row.timeout) as f64,
+    }
+}
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let middle = sorted.len() / 2;
+
+    Some(if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    })
+}
+
+/// Sample variance, which requires at least two values
+pub fn variance(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+
+    let mean = mean(values)?;
+
+    Some(
+        values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / (values.len() - 1) as f64,
+    )
+}
+
+/// largest number of differences for which the Wilcoxon signed-rank test is computed exactly
+const EXACT_WILCOXON: usize = 25;
+/// number of resamples of a bootstrap test
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+
+/// Two-sided p-value of the Wilcoxon signed-rank test that `differences` are centered around 0
+///
+/// Small samples use the exact distribution of the rank sum, larger ones the normal approximation
+/// with tie and continuity correction. Zero differences are dropped.
+pub fn wilcoxon(differences: &[f64]) -> f64 {
+    let mut differences = differences
+        .iter()
+        .copied()
+        .filter(|difference| *difference != 0.0)
+        .collect::<Vec<_>>();
+    differences.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+
+    // ranks are doubled to keep the mean ranks of ties integral
+    let mut ranks = Vec::with_capacity(differences.len());
+    let (mut positive, mut ties) = (0, 0.0);
+    let mut start = 0;
+
+    // tied absolute differences share the mean of their ranks
+    while start < differences.len() {
+        let end = start
+            + differences[start..]
+                .iter()
+                .take_while(|difference| difference.abs() == differences[start].abs())
+                .count();
+        let rank = start + end + 1;
+        let tied = (end - start) as f64;
+
+        positive += rank
+            * differences[start..end]
+                .iter()
+                .filter(|difference| **difference > 0.0)
+                .count();
+        ranks.extend(std::iter::repeat(rank).take(end - start));
+        ties += tied.powi(3) - tied;
+        start = end;
+    }
+
+    if ranks.is_empty() {
+        return 1.0;
+    }
+
+    if ranks.len() <= EXACT_WILCOXON {
+        // number of sign assignments by their (doubled) sum of positive ranks
+        let total = ranks.iter().sum::<usize>();
+        let mut counts = vec![0.0; total + 1];
+        counts[0] = 1.0;
+
+        for rank in ranks.iter().copied() {
+            for sum in (rank..=total).rev() {
+                counts[sum] += counts[sum - rank];
+            }
+        }
+
+        let assignments = 2f64.powi(ranks.len() as i32);
+        let lower = counts[..=positive].iter().sum::<f64>();
+        let upper = counts[positive..].iter().sum::<f64>();
+
+        return (2.0 * lower.min(upper) / assignments).min(1.0);
+    }
+
+    let n = ranks.len() as f64;
+    let positive = positive as f64 / 2.0;
+    let expected = n * (n + 1.0) / 4.0;
+    let deviation = (n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - ties / 48.0).sqrt();
+
+    if deviation == 0.0 {
+        return 1.0;
+    }
+
+    let z = ((positive - expected).abs() - 0.5).max(0.0) / deviation;
+
+    (2.0 * (1.0 - normal_cdf(z))).min(1.0)
+}
+
+/// Two-sided p-value of a bootstrap test that `baseline` and `candidate` have the same mean
+///
+/// Both samples are shifted to the pooled mean and resampled with a fixed seed, so the result is
+/// reproducible. Samples with less than two values can't be tested.
+pub fn bootstrap(baseline: &[f64], candidate: &[f64]) -> Option<f64> {
+    if baseline.len() < 2 || candidate.len() < 2 {
+        return None;
+    }
+
+    let (baseline_mean, candidate_mean) = (mean(baseline)?, mean(candidate)?);
+    let pooled = mean(&[baseline, candidate].concat())?;
+    let observed = (candidate_mean - baseline_mean).abs();
+
+    let shift = |values: &[f64], mean: f64| {
+        values
+            .iter()
+            .map(|value| value - mean + pooled)
+            .collect::<Vec<_>>()
+    };
+    let (baseline, candidate) = (
+        shift(baseline, baseline_mean),
+        shift(candidate, candidate_mean),
+    );
+
+    let mut random = SplitMix64(0);
+    let mut resample = |values: &[f64]| {
+        (0..values.len())
+            .map(|_| values[(random.next_u64() % values.len() as u64) as usize])
+            .sum::<f64>()
+            / values.len() as f64
+    };
+
+    // differences are compared with a tolerance against rounding errors of the shifted samples
+    let extreme = (0..BOOTSTRAP_RESAMPLES)
+        .filter(|_| {
+            let difference = resample(&candidate) - resample(&baseline);
+
+            difference.abs() >= observed * (1.0 - 1e-9)
+        })
+        .count();
+
+    Some((extreme + 1) as f64 / (BOOTSTRAP_RESAMPLES + 1) as f64)
+}
+
+/// Cumulative distribution function of the standard normal distribution
+fn normal_cdf(x: f64) -> f64 {
+    // Abramowitz and Stegun 7.1.26, the absolute error is below 1.5e-7
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+    let polynomial = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - polynomial * (-x * x / 2.0).exp();
+
+    if x >= 0.0 {
+        (1.0 + erf) / 2.0
+    } else {
+        (1.0 - erf) / 2.0
+    }
+}
+
+/// Render `rows` as a table aligned to `header`, the first `names` columns are left aligned
+pub fn table<I: Iterator<Item = Vec<String>>>(header: &[&str], names: usize, rows: I) -> String {
+    let header = header
+        .iter()
+        .map(|column| column.to_string())
+        .collect::<Vec<_>>();
+    let rows = rows.collect::<Vec<_>>();
+
+    let mut widths = header.iter().map(String::len).collect::<Vec<_>>();
+    for row in rows.iter() {
+        for (width, column) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(column.len());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (index, (column, width)) in row.iter().zip(widths.iter().copied()).enumerate() {
+            let _ = if index < names {
+                write!(table, "{column:<width$}  ")
+            } else {
+                write!(table, "{column:>width$}  ")
+            };
+        }
+
+        table.truncate(table.trim_end().len());
+        table.push('\n');
+    }
+
+    table
+}
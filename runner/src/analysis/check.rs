@@ -0,0 +1,133 @@
+use crate::database::{
+    export::{ExportFilter, ExportRow},
+    ConflictRow, ConnectionAdapter, ConnectionError, Satisfiability, ID,
+};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::warn;
+
+/// Instance that was answered with SAT and UNSAT by the runs of a benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub instance: String,
+    pub target: String,
+    // solvers that answered SAT respectively UNSAT in any iteration
+    pub satisfiable: BTreeSet<String>,
+    pub unsatisfiable: BTreeSet<String>,
+    // all runs that answered either
+    pub runs: Vec<ConflictRow>,
+}
+
+impl Conflict {
+    /// solvers whose iterations disagree with each other
+    pub fn inconsistent(&self) -> impl Iterator<Item = &String> {
+        self.satisfiable.intersection(&self.unsatisfiable)
+    }
+}
+
+/// Find all instances with conflicting answers
+///
+/// Instances are identified by their hash, test files without a hash by their path. Runs are
+/// considered regardless of their status or verification, a wrong answer is a bug either way.
+pub fn check(rows: &[ExportRow]) -> Vec<Conflict> {
+    let mut instances = BTreeMap::<&str, Vec<&ExportRow>>::new();
+
+    for row in rows.iter() {
+        if row.satisfiable != Satisfiability::Unknown as i32 {
+            instances
+                .entry(row.instance.as_deref().unwrap_or(&row.target))
+                .or_default()
+                .push(row);
+        }
+    }
+
+    instances
+        .into_iter()
+        .filter_map(|(instance, runs)| {
+            let solvers = |satisfiable: Satisfiability| {
+                let satisfiable = satisfiable as i32;
+
+                runs.iter()
+                    .filter(|row| row.satisfiable == satisfiable)
+                    .map(|row| row.solver.clone())
+                    .collect::<BTreeSet<_>>()
+            };
+
+            let satisfiable = solvers(Satisfiability::Satisfiable);
+            let unsatisfiable = solvers(Satisfiability::Unsatisfiable);
+
+            if satisfiable.is_empty() || unsatisfiable.is_empty() {
+                return None;
+            }
+
+            Some(Conflict {
+                instance: instance.to_owned(),
+                target: runs[0].target.clone(),
+                satisfiable,
+                unsatisfiable,
+                runs: runs
+                    .iter()
+                    .map(|row| ConflictRow {
+                        run: row.run,
+                        instance: instance.to_owned(),
+                        satisfiable: row.satisfiable as i8,
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Check all runs of `benchmark` and record the conflicts in the database
+///
+/// The conflicts table is missing in databases of older runners, their schema has to be upgraded
+/// explicitly first.
+pub fn check_benchmark(
+    connection: &ConnectionAdapter,
+    benchmark: ID,
+) -> Result<Vec<Conflict>, ConnectionError> {
+    connection.check_schema()?;
+
+    let rows = connection.export(&ExportFilter {
+        benchmark,
+        ..Default::default()
+    })?;
+    let conflicts = check(&rows);
+
+    connection.store_conflicts(
+        benchmark,
+        &conflicts
+            .iter()
+            .flat_map(|conflict| conflict.runs.iter().cloned())
+            .collect::<Vec<_>>(),
+    )?;
+
+    for conflict in conflicts.iter() {
+        warn!(
+            instance = %conflict.instance,
+            "Conflicting answers on {}: SAT by {}, UNSAT by {}",
+            conflict.target,
+            conflict.satisfiable.iter().join(", "),
+            conflict.unsatisfiable.iter().join(", ")
+        );
+    }
+
+    Ok(conflicts)
+}
+
+/// Render the conflicts as an aligned table
+pub fn table(conflicts: &[Conflict]) -> String {
+    super::table(
+        &["target", "SAT", "UNSAT", "inconsistent iterations", "runs"],
+        4,
+        conflicts.iter().map(|conflict| {
+            vec![
+                conflict.target.clone(),
+                conflict.satisfiable.iter().join(", "),
+                conflict.unsatisfiable.iter().join(", "),
+                conflict.inconsistent().join(", "),
+                conflict.runs.len().to_string(),
+            ]
+        }),
+    )
+}
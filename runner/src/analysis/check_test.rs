@@ -0,0 +1,65 @@
+use super::check::check;
+use crate::database::{export::ExportRow, ConflictRow};
+
+fn run(run: i32, solver: &str, target: &str, satisfiable: i32) -> ExportRow {
+    ExportRow {
+        run,
+        satisfiable,
+        ..ExportRow::fixture(solver, target)
+    }
+}
+
+#[test]
+fn check_conflicts() {
+    let mut hashed = run(7, "minisat", "copy/a.cnf", -1);
+    hashed.instance = Some("a".to_owned());
+
+    let rows = vec![
+        run(1, "cadical", "a.cnf", 1),
+        run(2, "kissat", "a.cnf", 1),
+        run(3, "cadical", "b.cnf", -1),
+        run(4, "cadical", "b.cnf", 1),
+        run(5, "kissat", "b.cnf", 0),
+        run(6, "kissat", "c.cnf", 1),
+        // unknown answers never conflict
+        run(8, "cadical", "c.cnf", 0),
+    ];
+
+    let conflicts = check(&rows);
+
+    // iterations of the same solver disagree
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].target, "b.cnf");
+    assert_eq!(
+        conflicts[0].inconsistent().collect::<Vec<_>>(),
+        vec!["cadical"]
+    );
+    assert_eq!(
+        conflicts[0].runs,
+        vec![
+            ConflictRow {
+                run: 3,
+                instance: "b.cnf".to_owned(),
+                satisfiable: -1,
+            },
+            ConflictRow {
+                run: 4,
+                instance: "b.cnf".to_owned(),
+                satisfiable: 1,
+            },
+        ]
+    );
+
+    // solvers disagree on an instance identified by its hash instead of its path
+    let mut rows = rows;
+    rows[0].instance = Some("a".to_owned());
+    rows[1].instance = Some("a".to_owned());
+    rows.push(hashed);
+
+    let conflicts = check(&rows);
+
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0].instance, "a");
+    assert_eq!(conflicts[0].runs.len(), 3);
+    assert_eq!(conflicts[0].inconsistent().count(), 0);
+}
@@ -0,0 +1,299 @@
+use super::{bootstrap, penalized_runtime, solved, wilcoxon};
+use crate::database::{export::ExportRow, Satisfiability};
+use serde::Serialize;
+use std::{collections::BTreeMap, path::Path};
+
+/// Runs of a single solver on a single instance in both benchmarks
+///
+/// Instances are identified by their hash, test files without a hash by their path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InstanceComparison {
+    pub solver: String,
+    pub instance: String,
+    pub target: String,
+    pub baseline_solved: bool,
+    pub candidate_solved: bool,
+    // mean PAR-2 runtime of the iterations in ms
+    pub baseline_par2: f64,
+    pub candidate_par2: f64,
+    // baseline / candidate, values above 1 are speedups
+    pub speedup: f64,
+    // bootstrap test of the PAR-2 runtimes of the iterations, requires two iterations of each
+    pub p_value: Option<f64>,
+    // the benchmarks answered SAT and UNSAT
+    pub disagreement: bool,
+}
+
+/// Comparison of a solver on all instances it ran on in both benchmarks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolverComparison {
+    pub solver: String,
+    pub instances: usize,
+    pub faster: usize,
+    pub slower: usize,
+    pub newly_solved: usize,
+    pub newly_unsolved: usize,
+    pub disagreements: usize,
+    pub baseline_par2: f64,
+    pub candidate_par2: f64,
+    // relative change of the PAR-2 score, positive values are slowdowns
+    pub change: f64,
+    // Wilcoxon signed-rank test of the mean PAR-2 runtimes per instance
+    pub p_value: f64,
+    pub regression: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    pub instances: Vec<InstanceComparison>,
+    pub solvers: Vec<SolverComparison>,
+}
+
+impl InstanceComparison {
+    /// whether the instance became faster or slower by more than `threshold`
+    pub fn changed(&self, threshold: f64) -> bool {
+        self.baseline_solved != self.candidate_solved
+            || self.disagreement
+            || self.speedup > 1.0 + threshold
+            || self.speedup < 1.0 / (1.0 + threshold)
+    }
+
+    /// whether the runtimes differ with a p-value below `alpha`, untestable instances always do
+    pub fn significant(&self, alpha: f64) -> bool {
+        self.p_value.map_or(true, |p_value| p_value < alpha)
+    }
+}
+
+/// Runs of every solver by instance
+fn group(rows: &[ExportRow]) -> BTreeMap<(&str, &str), Vec<&ExportRow>> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+
+    for row in rows {
+        let instance = row.instance.as_deref().unwrap_or(&row.target);
+
+        groups
+            .entry((row.solver.as_str(), instance))
+            .or_default()
+            .push(row);
+    }
+
+    groups
+}
+
+/// Whether the majority of the iterations was solved
+fn majority_solved(runs: &[&ExportRow]) -> bool {
+    runs.iter().filter(|row| solved(row)).count() * 2 > runs.len()
+}
+
+fn par2(runs: &[&ExportRow]) -> Vec<f64> {
+    runs.iter().map(|row| penalized_runtime(row, 2)).collect()
+}
+
+fn answered(runs: &[&ExportRow], satisfiable: Satisfiability) -> bool {
+    let satisfiable = satisfiable as i32;
+
+    runs.iter()
+        .any(|row| solved(row) && row.satisfiable == satisfiable)
+}
+
+/// Compare the runs of `candidate` to those of `baseline` by solver and instance
+///
+/// A solver regressed if it disagrees with the baseline on any answer or its PAR-2 score on the
+/// shared instances increased by more than `threshold` with a p-value below `alpha`. Instances
+/// only count as faster or slower if their iterations differ significantly as well. Instances only
+/// run in one of the benchmarks are ignored.
+pub fn compare(
+    baseline: &[ExportRow],
+    candidate: &[ExportRow],
+    threshold: f64,
+    alpha: f64,
+) -> Comparison {
+    let baseline = group(baseline);
+    let mut comparison = Comparison::default();
+
+    for ((solver, instance), candidate_runs) in group(candidate) {
+        let Some(baseline_runs) = baseline.get(&(solver, instance)) else {
+            continue;
+        };
+
+        let (baseline_runtimes, candidate_runtimes) = (par2(baseline_runs), par2(&candidate_runs));
+        let (baseline_par2, candidate_par2) = (
+            super::mean(&baseline_runtimes).unwrap_or_default(),
+            super::mean(&candidate_runtimes).unwrap_or_default(),
+        );
+
+        comparison.instances.push(InstanceComparison {
+            solver: solver.to_owned(),
+            instance: instance.to_owned(),
+            target: candidate_runs[0].target.clone(),
+            baseline_solved: majority_solved(baseline_runs),
+            candidate_solved: majority_solved(&candidate_runs),
+            baseline_par2,
+            candidate_par2,
+            // runtimes are in ms, instant runs don't divide by zero
+            speedup: baseline_par2 / candidate_par2.max(1.0),
+            p_value: bootstrap(&baseline_runtimes, &candidate_runtimes),
+            disagreement: (answered(baseline_runs, Satisfiability::Satisfiable)
+                && answered(&candidate_runs, Satisfiability::Unsatisfiable))
+                || (answered(baseline_runs, Satisfiability::Unsatisfiable)
+                    && answered(&candidate_runs, Satisfiability::Satisfiable)),
+        });
+    }
+
+    let mut solvers = BTreeMap::<&str, Vec<&InstanceComparison>>::new();
+    for instance in comparison.instances.iter() {
+        solvers.entry(&instance.solver).or_default().push(instance);
+    }
+
+    comparison.solvers = solvers
+        .into_iter()
+        .map(|(solver, instances)| {
+            let count = |predicate: &dyn Fn(&InstanceComparison) -> bool| {
+                instances
+                    .iter()
+                    .filter(|&&instance| predicate(instance))
+                    .count()
+            };
+            let mean = |par2: &dyn Fn(&InstanceComparison) -> f64| {
+                instances
+                    .iter()
+                    .map(|&instance| par2(instance))
+                    .sum::<f64>()
+                    / instances.len() as f64
+            };
+
+            let baseline_par2 = mean(&|instance| instance.baseline_par2);
+            let candidate_par2 = mean(&|instance| instance.candidate_par2);
+            let change = candidate_par2 / baseline_par2.max(1.0) - 1.0;
+            let p_value = wilcoxon(
+                &instances
+                    .iter()
+                    .map(|instance| instance.candidate_par2 - instance.baseline_par2)
+                    .collect::<Vec<_>>(),
+            );
+            let disagreements = count(&|instance| instance.disagreement);
+
+            SolverComparison {
+                solver: solver.to_owned(),
+                instances: instances.len(),
+                faster: count(&|instance| {
+                    instance.speedup > 1.0 + threshold && instance.significant(alpha)
+                }),
+                slower: count(&|instance| {
+                    instance.speedup < 1.0 / (1.0 + threshold) && instance.significant(alpha)
+                }),
+                newly_solved: count(&|instance| {
+                    !instance.baseline_solved && instance.candidate_solved
+                }),
+                newly_unsolved: count(&|instance| {
+                    instance.baseline_solved && !instance.candidate_solved
+                }),
+                disagreements,
+                baseline_par2,
+                candidate_par2,
+                change,
+                p_value,
+                regression: disagreements > 0 || (change > threshold && p_value < alpha),
+            }
+        })
+        .collect();
+
+    comparison
+}
+
+/// Render the instances that changed by more than `threshold` as an aligned table
+pub fn instance_table(comparison: &Comparison, threshold: f64) -> String {
+    let status = |solved: bool| (if solved { "solved" } else { "unsolved" }).to_owned();
+
+    super::table(
+        &[
+            "solver",
+            "target",
+            "baseline",
+            "candidate",
+            "baseline PAR-2",
+            "candidate PAR-2",
+            "speedup",
+            "p-value",
+            "answer",
+        ],
+        2,
+        comparison
+            .instances
+            .iter()
+            .filter(|instance| instance.changed(threshold))
+            .map(|instance| {
+                vec![
+                    instance.solver.clone(),
+                    instance.target.clone(),
+                    status(instance.baseline_solved),
+                    status(instance.candidate_solved),
+                    format!("{:.0}", instance.baseline_par2),
+                    format!("{:.0}", instance.candidate_par2),
+                    format!("{:.2}x", instance.speedup),
+                    instance
+                        .p_value
+                        .map_or("-".to_owned(), |p_value| format!("{p_value:.4}")),
+                    if instance.disagreement {
+                        "DISAGREES".to_owned()
+                    } else {
+                        String::new()
+                    },
+                ]
+            }),
+    )
+}
+
+/// Render the solver comparisons as an aligned table, durations are in ms
+pub fn solver_table(comparison: &Comparison) -> String {
+    super::table(
+        &[
+            "solver",
+            "instances",
+            "faster",
+            "slower",
+            "newly solved",
+            "newly unsolved",
+            "disagreements",
+            "baseline PAR-2",
+            "candidate PAR-2",
+            "change",
+            "p-value",
+            "",
+        ],
+        1,
+        comparison.solvers.iter().map(|solver| {
+            vec![
+                solver.solver.clone(),
+                solver.instances.to_string(),
+                solver.faster.to_string(),
+                solver.slower.to_string(),
+                solver.newly_solved.to_string(),
+                solver.newly_unsolved.to_string(),
+                solver.disagreements.to_string(),
+                format!("{:.0}", solver.baseline_par2),
+                format!("{:.0}", solver.candidate_par2),
+                format!("{:+.1}%", solver.change * 100.0),
+                format!("{:.4}", solver.p_value),
+                if solver.regression {
+                    "REGRESSION".to_owned()
+                } else {
+                    String::new()
+                },
+            ]
+        }),
+    )
+}
+
+/// Write the comparison of every instance as CSV to `path`
+pub fn write_instances(comparison: &Comparison, path: &Path) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    for instance in comparison.instances.iter() {
+        writer.serialize(instance)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use super::{
+    bootstrap,
+    compare::{compare, Comparison},
+    wilcoxon,
+};
+use crate::database::export::ExportRow;
+
+/// run of `solver` on `instance`, runs without runtime timed out
+fn run(solver: &str, instance: &str, runtime: Option<i64>, satisfiable: i32) -> ExportRow {
+    ExportRow {
+        instance: Some(instance.to_owned()),
+        runtime,
+        satisfiable: if runtime.is_some() { satisfiable } else { 0 },
+        status: if runtime.is_some() { 0 } else { 1 },
+        ..ExportRow::fixture(solver, &format!("{instance}.cnf"))
+    }
+}
+
+fn runs(runtimes: &[Option<i64>]) -> Vec<ExportRow> {
+    runtimes
+        .iter()
+        .enumerate()
+        .map(|(index, runtime)| run("cadical", &format!("{index:02}"), *runtime, 1))
+        .collect()
+}
+
+#[test]
+fn wilcoxon_signed_rank() {
+    assert_eq!(wilcoxon(&[]), 1.0);
+    assert_eq!(wilcoxon(&[0.0, 0.0]), 1.0);
+
+    // all ten differences are positive, the exact two-sided p-value is 2 / 2^10
+    let p = wilcoxon(&(1..=10).map(f64::from).collect::<Vec<_>>());
+    assert_eq!(p, 2.0 / 1024.0);
+
+    let p = wilcoxon(&[1.0, -2.0, 3.0, -4.0, 5.0, -6.0]);
+    assert_eq!(p, 0.84375);
+
+    // ties share the mean of their ranks in the exact distribution as well
+    let p = wilcoxon(&[1.0, 1.0, 1.0, 1.0, -1.0]);
+    assert_eq!(p, 0.375);
+
+    // larger samples fall back to the normal approximation
+    let p = wilcoxon(&(1..=30).map(f64::from).collect::<Vec<_>>());
+    assert!(p < 1e-5, "p = {p}");
+}
+
+#[test]
+fn bootstrap_iterations() {
+    assert_eq!(bootstrap(&[10.0], &[20.0, 30.0]), None);
+    assert_eq!(bootstrap(&[10.0, 10.0], &[10.0, 10.0]), Some(1.0));
+
+    let p = bootstrap(
+        &[10.0, 11.0, 12.0, 10.0, 11.0],
+        &[20.0, 21.0, 22.0, 20.0, 21.0],
+    )
+    .unwrap();
+    assert!(p < 0.01, "p = {p}");
+
+    let p = bootstrap(&[10.0, 50.0, 30.0], &[50.0, 20.0, 40.0]).unwrap();
+    assert!(p > 0.5, "p = {p}");
+}
+
+#[test]
+fn compare_benchmarks() {
+    // ten instances take twice as long, one is newly solved and one newly unsolved
+    let mut baseline = runs(&[Some(10); 12]);
+    baseline[10] = run("cadical", "10", None, 1);
+    baseline[11] = run("cadical", "11", Some(50), 1);
+
+    let mut candidate = runs(&[Some(20); 12]);
+    candidate[10] = run("cadical", "10", Some(90), 1);
+    candidate[11] = run("cadical", "11", None, 1);
+    // only run by the candidate
+    candidate.push(run("cadical", "new", Some(1), 1));
+
+    let Comparison { instances, solvers } = compare(&baseline, &candidate, 0.05, 0.05);
+
+    assert_eq!(instances.len(), 12);
+    assert_eq!(solvers.len(), 1);
+    assert_eq!(solvers[0].slower, 11);
+    assert_eq!(solvers[0].faster, 1);
+    assert_eq!(solvers[0].newly_solved, 1);
+    assert_eq!(solvers[0].newly_unsolved, 1);
+    assert_eq!(solvers[0].disagreements, 0);
+    assert!(solvers[0].regression);
+
+    // the same runs never regress
+    let Comparison { solvers, .. } = compare(&baseline, &baseline, 0.05, 0.05);
+    assert!(!solvers[0].regression);
+
+    // a single wrong answer is a regression regardless of the runtime
+    let mut disagreeing = baseline.clone();
+    disagreeing[0].satisfiable = -1;
+
+    let Comparison { instances, solvers } = compare(&baseline, &disagreeing, 0.05, 0.05);
+    assert!(instances[0].disagreement);
+    assert_eq!(solvers[0].change, 0.0);
+    assert!(solvers[0].regression);
+}
+
+#[test]
+fn compare_noisy_iterations() {
+    // the mean runtime increases by 17%, but the iterations vary too much to tell
+    let baseline = vec![
+        run("cadical", "a", Some(10), 1),
+        run("cadical", "a", Some(50), 1),
+    ];
+    let candidate = vec![
+        run("cadical", "a", Some(50), 1),
+        run("cadical", "a", Some(20), 1),
+    ];
+
+    let Comparison { instances, solvers } = compare(&baseline, &candidate, 0.05, 0.05);
+
+    assert!(instances[0].speedup < 1.0);
+    assert!(instances[0].p_value.unwrap() > 0.05);
+    assert_eq!(solvers[0].slower, 0);
+    assert!(!solvers[0].regression);
+}
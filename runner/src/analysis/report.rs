@@ -0,0 +1,217 @@
+use super::{mean, median, penalized_runtime, solved, variance};
+use crate::database::{export::ExportRow, RunStatus, Satisfiability};
+use serde::Serialize;
+use std::{collections::BTreeMap, path::Path};
+
+/// Name of the solver that picks the fastest solver for every test file of a test set
+pub const VIRTUAL_BEST: &str = "virtual best";
+
+/// Runs of a single solver on a test set by test file, one per iteration
+pub type Targets<'a> = BTreeMap<&'a str, Vec<&'a ExportRow>>;
+
+/// Statistics of a solver on a test set
+///
+/// Counts are per run, i.e., a test file is counted once per iteration. Runtimes are averaged
+/// over the iterations of a test file first, the variance is that of the iterations.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub test_set: String,
+    pub solver: String,
+    pub runs: usize,
+    pub satisfiable: usize,
+    pub unsatisfiable: usize,
+    pub timeouts: usize,
+    // unsolved runs that didn't time out, e.g., crashes or rejected answers
+    pub failed: usize,
+    pub par2: f64,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    pub variance: Option<f64>,
+}
+
+/// Point of a cactus plot, the `solved`th fastest solved run took `runtime` ms
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CactusPoint {
+    pub test_set: String,
+    pub solver: String,
+    pub solved: usize,
+    pub runtime: i64,
+}
+
+/// Group runs by test set and solver, each test set is followed by its virtual best solver
+///
+/// The virtual best solver uses the runs of the solver with the lowest PAR-2 score per test file.
+pub fn group(rows: &[ExportRow]) -> Vec<((&str, &str), Targets)> {
+    let mut test_sets = BTreeMap::<&str, BTreeMap<&str, Targets>>::new();
+
+    for row in rows {
+        test_sets
+            .entry(&row.test_set)
+            .or_default()
+            .entry(&row.solver)
+            .or_default()
+            .entry(&row.target)
+            .or_default()
+            .push(row);
+    }
+
+    let mut grouped = Vec::new();
+
+    for (test_set, solvers) in test_sets {
+        let mut best = Targets::new();
+
+        for (target, runs) in solvers.values().flatten() {
+            if best
+                .get(target)
+                .map_or(true, |current| par2(runs) < par2(current))
+            {
+                best.insert(*target, runs.clone());
+            }
+        }
+
+        grouped.extend(
+            solvers
+                .into_iter()
+                .map(|(solver, targets)| ((test_set, solver), targets)),
+        );
+        grouped.push(((test_set, VIRTUAL_BEST), best));
+    }
+
+    grouped
+}
+
+fn par2(runs: &[&ExportRow]) -> f64 {
+    runs.iter()
+        .map(|row| penalized_runtime(row, 2))
+        .sum::<f64>()
+        / runs.len() as f64
+}
+
+pub fn summarize(test_set: &str, solver: &str, targets: &Targets) -> Summary {
+    let runs = targets.values().flatten().copied().collect::<Vec<_>>();
+    let count = |satisfiable: Satisfiability| {
+        runs.iter()
+            .filter(|row| solved(row) && row.satisfiable == satisfiable as i32)
+            .count()
+    };
+
+    let satisfiable = count(Satisfiability::Satisfiable);
+    let unsatisfiable = count(Satisfiability::Unsatisfiable);
+    let timeouts = runs
+        .iter()
+        .filter(|row| !solved(row) && row.status == RunStatus::Timeout.kind() as i32)
+        .count();
+
+    // runtimes of the solved iterations of every test file
+    let runtimes = targets
+        .values()
+        .map(|iterations| {
+            iterations
+                .iter()
+                .filter(|row| solved(row))
+                .filter_map(|row| row.runtime.map(|runtime| runtime as f64))
+                .collect::<Vec<_>>()
+        })
+        .filter(|runtimes| !runtimes.is_empty())
+        .collect::<Vec<_>>();
+    let means = runtimes
+        .iter()
+        .filter_map(|runtimes| mean(runtimes))
+        .collect::<Vec<_>>();
+    let variances = runtimes
+        .iter()
+        .filter_map(|runtimes| variance(runtimes))
+        .collect::<Vec<_>>();
+
+    Summary {
+        test_set: test_set.to_owned(),
+        solver: solver.to_owned(),
+        runs: runs.len(),
+        satisfiable,
+        unsatisfiable,
+        timeouts,
+        failed: runs.len() - satisfiable - unsatisfiable - timeouts,
+        par2: par2(&runs),
+        mean: mean(&means),
+        median: median(&means),
+        variance: mean(&variances),
+    }
+}
+
+/// Summaries of all solvers and the virtual best solver of every test set
+pub fn report(rows: &[ExportRow]) -> Vec<Summary> {
+    group(rows)
+        .iter()
+        .map(|((test_set, solver), targets)| summarize(test_set, solver, targets))
+        .collect()
+}
+
+/// Solved runs of every solver on every test set ordered by their runtime
+pub fn cactus(rows: &[ExportRow]) -> Vec<CactusPoint> {
+    let mut points = Vec::new();
+
+    for ((test_set, solver), targets) in group(rows) {
+        let mut runtimes = targets
+            .values()
+            .flatten()
+            .filter(|row| solved(row))
+            .filter_map(|row| row.runtime)
+            .collect::<Vec<_>>();
+        runtimes.sort_unstable();
+
+        points.extend(
+            runtimes
+                .into_iter()
+                .enumerate()
+                .map(|(index, runtime)| CactusPoint {
+                    test_set: test_set.to_owned(),
+                    solver: solver.to_owned(),
+                    solved: index + 1,
+                    runtime,
+                }),
+        );
+    }
+
+    points
+}
+
+/// Render the summaries as an aligned table, durations are in ms
+pub fn table(summaries: &[Summary]) -> String {
+    let optional = |value: Option<f64>| value.map_or("-".to_owned(), |value| format!("{value:.0}"));
+
+    super::table(
+        &[
+            "test set", "solver", "runs", "SAT", "UNSAT", "timeouts", "failed", "PAR-2", "mean",
+            "median", "variance",
+        ],
+        2,
+        summaries.iter().map(|summary| {
+            vec![
+                summary.test_set.clone(),
+                summary.solver.clone(),
+                summary.runs.to_string(),
+                summary.satisfiable.to_string(),
+                summary.unsatisfiable.to_string(),
+                summary.timeouts.to_string(),
+                summary.failed.to_string(),
+                format!("{:.0}", summary.par2),
+                optional(summary.mean),
+                optional(summary.median),
+                optional(summary.variance),
+            ]
+        }),
+    )
+}
+
+/// Write the cactus plot data of `rows` as CSV to `path`
+pub fn write_cactus(rows: &[ExportRow], path: &Path) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    for point in cactus(rows) {
+        writer.serialize(point)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use super::report::{cactus, report, CactusPoint, VIRTUAL_BEST};
+use crate::database::export::ExportRow;
+
+/// run of `solver` on `target`, runs without runtime timed out
+fn run(solver: &str, target: &str, runtime: Option<i64>) -> ExportRow {
+    ExportRow {
+        runtime,
+        satisfiable: if runtime.is_some() { 1 } else { 0 },
+        status: if runtime.is_some() { 0 } else { 1 },
+        ..ExportRow::fixture(solver, target)
+    }
+}
+
+#[test]
+fn report_solvers() {
+    let rows = vec![
+        run("cadical", "a.cnf", Some(10)),
+        run("cadical", "a.cnf", Some(20)),
+        run("cadical", "b.cnf", None),
+        run("cadical", "b.cnf", None),
+        run("kissat", "a.cnf", Some(40)),
+        run("kissat", "a.cnf", Some(40)),
+        run("kissat", "b.cnf", Some(50)),
+        run("kissat", "b.cnf", Some(70)),
+    ];
+
+    let summaries = report(&rows);
+    let solvers = summaries
+        .iter()
+        .map(|summary| summary.solver.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(solvers, vec!["cadical", "kissat", VIRTUAL_BEST]);
+
+    // timeouts are penalized with twice the timeout
+    assert_eq!(summaries[0].satisfiable, 2);
+    assert_eq!(summaries[0].timeouts, 2);
+    assert_eq!(summaries[0].par2, (10.0 + 20.0 + 200.0 + 200.0) / 4.0);
+    assert_eq!(summaries[0].mean, Some(15.0));
+    assert_eq!(summaries[0].variance, Some(50.0));
+
+    assert_eq!(summaries[1].par2, 50.0);
+    assert_eq!(summaries[1].median, Some(50.0));
+
+    // the virtual best solver picks cadical for a.cnf and kissat for b.cnf
+    assert_eq!(summaries[2].satisfiable, 4);
+    assert_eq!(summaries[2].par2, (10.0 + 20.0 + 50.0 + 70.0) / 4.0);
+
+    let best = cactus(&rows)
+        .into_iter()
+        .filter(|point| point.solver == VIRTUAL_BEST)
+        .collect::<Vec<_>>();
+
+    assert_eq!(best.len(), 4);
+    assert_eq!(
+        best[3],
+        CactusPoint {
+            test_set: "sat".to_owned(),
+            solver: VIRTUAL_BEST.to_owned(),
+            solved: 4,
+            runtime: 70,
+        }
+    );
+}
@@ -22,6 +22,13 @@ use std::{
 };
 use tracing::{debug, error, info, span, trace, warn, Level};
 
+pub mod dimacs;
+#[cfg(test)]
+mod dimacs_test;
+pub mod gbd;
+#[cfg(test)]
+mod gbd_test;
+
 /// map of testname -> Collector
 pub type CollectorMap = BTreeMap<CowStr, Collector>;
 
@@ -68,8 +75,19 @@ impl Deref for PathValue {
     }
 }
 
+impl PathValue {
+    /// mark the path as unfinished, wrapped paths then restore their original name
+    pub fn interrupt(&self) {
+        match self {
+            Self::Buf(_) => (),
+            #[cfg(feature = "distributed")]
+            Self::Wrapped(wrapped) => wrapped.interrupt(),
+        }
+    }
+}
+
 /// primitve way to retrieve the tmp dir from the environment with defualt to /tmp
-fn get_tmp_dir() -> PathBuf {
+pub(crate) fn get_tmp_dir() -> PathBuf {
     env::var("TMPDIR")
         .map(PathBuf::from)
         .unwrap_or(PathBuf::from("/tmp"))
@@ -79,15 +97,20 @@ impl<'a> Collector {
     pub fn load(config: &CollectorConfig) -> Result<Self, ConfigErrors> {
         // TODO: Inject FS and MPI collector here
         match config {
-            CollectorConfig::GDB { server, tmp_dir } => {
-                error!("The GDB isn't implemented yet, please use Glob instead");
-                info!(
-                    "Would save GDB tests to {:?} from {}",
-                    tmp_dir.clone().unwrap_or_else(get_tmp_dir),
-                    server
-                );
+            CollectorConfig::GDB {
+                server,
+                tmp_dir,
+                hashes,
+                query,
+            } => {
+                let tmp_dir = tmp_dir
+                    .clone()
+                    .unwrap_or_else(|| get_tmp_dir().join("satan-gbd"));
+                info!(server = %server, "Saving GBD tests to {tmp_dir:?}");
 
-                Ok(Self::GDB { paths: Vec::new() })
+                Ok(Self::GDB {
+                    paths: gbd::load(server, &tmp_dir, hashes, query.as_ref())?,
+                })
             }
             // NOTE: this is a stub because the grouped collectors can only be resolved once all
             // other collectors are built
@@ -183,7 +206,7 @@ impl Iterator for Collector {
             #[cfg(feature = "distributed")]
             Self::FS { inner } => inner.size_hint(),
             #[cfg(feature = "distributed")]
-            Self::MPI(_collector) => todo!(),
+            Self::MPI(collector) => collector.size_hint(),
         }
     }
 
@@ -274,7 +297,7 @@ impl Iterator for Collector {
                 None
             }
             #[cfg(feature = "distributed")]
-            Self::MPI(_collector) => todo!(),
+            Self::MPI(collector) => collector.next(),
         }
     }
 }
@@ -0,0 +1,247 @@
+use super::gbd::decompress;
+use md5::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Debug, Error)]
+pub enum DimacsError {
+    #[error("Failed to read instance")]
+    IO(#[from] io::Error),
+    #[error("Missing or invalid `p cnf` header")]
+    InvalidHeader,
+    #[error("Invalid literal: {0:?}")]
+    InvalidLiteral(String),
+}
+
+/// Structural features of a CNF instance, identified by its GBD hash
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceFeatures {
+    pub hash: String,
+    // counts as found in the formula, the header is only used for validation
+    pub variables: u32,
+    pub clauses: u64,
+    pub literals: u64,
+    pub header_variables: u32,
+    pub header_clauses: u64,
+    // clauses per variable
+    pub ratio: f64,
+    // fraction of clauses with at most one positive literal
+    pub horn_fraction: f64,
+    pub binary_fraction: f64,
+    // number of clauses per clause length
+    pub clause_lengths: BTreeMap<usize, u64>,
+    // occurrences of the variables 1..=variables in all clauses
+    pub min_occurrences: u64,
+    pub max_occurrences: u64,
+    pub mean_occurrences: f64,
+    pub stddev_occurrences: f64,
+}
+
+/// Iterator over the clauses of a DIMACS CNF file
+///
+/// Clauses may span multiple lines and multiple clauses may share a line, comments are skipped and
+/// `%` ends the formula as in some older benchmark sets.
+#[derive(Debug)]
+pub struct ClauseReader<R> {
+    reader: R,
+    line: String,
+    position: usize,
+    header: Option<(u32, u64)>,
+    done: bool,
+}
+
+impl ClauseReader<Box<dyn BufRead>> {
+    /// open a plain, gzip, xz or bzip2 compressed CNF file
+    pub fn open(path: &Path) -> Result<Self, DimacsError> {
+        let reader = decompress(BufReader::new(File::open(path)?))?;
+
+        Ok(Self::new(Box::new(BufReader::new(reader))))
+    }
+}
+
+impl<R: BufRead> ClauseReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            position: 0,
+            header: None,
+            done: false,
+        }
+    }
+
+    /// variables and clauses declared by the `p cnf` header, if it was read already
+    pub fn header(&self) -> Option<(u32, u64)> {
+        self.header
+    }
+
+    /// next literal of the current line
+    fn next_literal(&mut self) -> Option<Result<i32, DimacsError>> {
+        let rest = &self.line[self.position..];
+        let start = rest.find(|c: char| !c.is_whitespace())?;
+        let rest = &rest[start..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..end];
+
+        let literal = token
+            .parse::<i32>()
+            .map_err(|_| DimacsError::InvalidLiteral(token.to_owned()));
+        self.position += start + end;
+
+        Some(literal)
+    }
+
+    fn parse_header(line: &str) -> Option<(u32, u64)> {
+        let mut fields = line.split_whitespace();
+
+        match (fields.next(), fields.next()) {
+            (Some("p"), Some("cnf")) => {
+                Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ClauseReader<R> {
+    type Item = Result<Vec<i32>, DimacsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut clause = Vec::new();
+
+        while !self.done {
+            while let Some(literal) = self.next_literal() {
+                match literal {
+                    Ok(0) => return Some(Ok(clause)),
+                    Ok(literal) => clause.push(literal),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            self.line.clear();
+            self.position = 0;
+
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => (),
+                Err(e) => return Some(Err(e.into())),
+            }
+
+            let line = self.line.trim_start();
+
+            if line.starts_with('c') {
+                self.position = self.line.len();
+            } else if line.starts_with('p') {
+                match Self::parse_header(line) {
+                    Some(header) if self.header.is_none() => self.header = Some(header),
+                    _ => return Some(Err(DimacsError::InvalidHeader)),
+                }
+
+                self.position = self.line.len();
+            } else if line.starts_with('%') {
+                self.done = true;
+            }
+        }
+
+        // the last clause may lack its terminating 0
+        if clause.is_empty() {
+            None
+        } else {
+            Some(Ok(clause))
+        }
+    }
+}
+
+/// Parse a (compressed) CNF file and compute its features
+///
+/// The hash is compatible with GBD: the MD5 sum of all clauses with a single space after every
+/// literal and `0\n` after every clause, i.e., without comments, header and extra whitespace.
+pub fn parse(path: &Path) -> Result<InstanceFeatures, DimacsError> {
+    let mut reader = ClauseReader::open(path)?;
+    let mut features = InstanceFeatures::default();
+    let mut hash = Context::new();
+    let mut buffer = String::new();
+    let mut occurrences: Vec<u64> = vec![0];
+    let (mut horn, mut binary) = (0, 0);
+
+    for clause in reader.by_ref() {
+        let clause = clause?;
+
+        buffer.clear();
+
+        for literal in clause.iter() {
+            write!(buffer, "{literal} ").unwrap();
+
+            let variable = literal.unsigned_abs() as usize;
+
+            if variable >= occurrences.len() {
+                occurrences.resize(variable + 1, 0);
+            }
+
+            occurrences[variable] += 1;
+        }
+
+        buffer.push_str("0\n");
+        hash.consume(buffer.as_bytes());
+
+        features.clauses += 1;
+        features.literals += clause.len() as u64;
+        *features.clause_lengths.entry(clause.len()).or_default() += 1;
+
+        if clause.iter().filter(|literal| **literal > 0).count() <= 1 {
+            horn += 1;
+        }
+
+        if clause.len() == 2 {
+            binary += 1;
+        }
+    }
+
+    let (header_variables, header_clauses) = reader.header().ok_or(DimacsError::InvalidHeader)?;
+
+    features.hash = format!("{:x}", hash.compute());
+    features.header_variables = header_variables;
+    features.header_clauses = header_clauses;
+    features.variables = (occurrences.len() - 1) as u32;
+
+    if features.header_clauses != features.clauses || features.header_variables < features.variables
+    {
+        warn!(
+            path = ?path,
+            "Header declares {header_variables} variables and {header_clauses} clauses, found {} variables and {} clauses",
+            features.variables,
+            features.clauses
+        );
+    }
+
+    if features.clauses > 0 {
+        features.horn_fraction = horn as f64 / features.clauses as f64;
+        features.binary_fraction = binary as f64 / features.clauses as f64;
+    }
+
+    if features.variables > 0 {
+        let variables = features.variables as f64;
+        let occurrences = &occurrences[1..];
+
+        features.ratio = features.clauses as f64 / variables;
+        features.min_occurrences = occurrences.iter().copied().min().unwrap_or_default();
+        features.max_occurrences = occurrences.iter().copied().max().unwrap_or_default();
+        features.mean_occurrences = features.literals as f64 / variables;
+        features.stddev_occurrences = (occurrences
+            .iter()
+            .map(|count| (*count as f64 - features.mean_occurrences).powi(2))
+            .sum::<f64>()
+            / variables)
+            .sqrt();
+    }
+
+    Ok(features)
+}
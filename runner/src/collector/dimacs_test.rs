@@ -0,0 +1,64 @@
+use super::dimacs::{parse, ClauseReader, DimacsError};
+use crate::test_util::TempPath;
+use bzip2::{write::BzEncoder, Compression};
+use std::io::Write;
+
+const CNF: &str = "c example\np cnf 4 4\n1 -2 0\n2 3 -4 0 -1\n-3 0\n3 0\n";
+
+#[test]
+fn clause_reader_splits_clauses() {
+    let mut reader = ClauseReader::new(CNF.as_bytes());
+    let clauses = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(
+        clauses,
+        vec![vec![1, -2], vec![2, 3, -4], vec![-1, -3], vec![3]]
+    );
+    assert_eq!(reader.header(), Some((4, 4)));
+}
+
+#[test]
+fn clause_reader_rejects_invalid_literals() {
+    let mut reader = ClauseReader::new("p cnf 1 1\n1 x 0\n".as_bytes());
+
+    assert!(matches!(
+        reader.next(),
+        Some(Err(DimacsError::InvalidLiteral(_)))
+    ));
+}
+
+#[test]
+fn parse_features() {
+    let path = TempPath::with_content("plain.cnf", CNF.as_bytes());
+    let features = parse(&path).unwrap();
+
+    // md5 of "1 -2 0\n2 3 -4 0\n-1 -3 0\n3 0\n"
+    assert_eq!(features.hash, "e485a964204306dc58236bc8b028d88f");
+    assert_eq!(features.variables, 4);
+    assert_eq!(features.clauses, 4);
+    assert_eq!(features.literals, 8);
+    assert_eq!(features.ratio, 1.0);
+    assert_eq!(features.horn_fraction, 0.75);
+    assert_eq!(features.binary_fraction, 0.5);
+    assert_eq!(features.clause_lengths.get(&2), Some(&2));
+    assert_eq!(features.min_occurrences, 1);
+    assert_eq!(features.max_occurrences, 3);
+    assert_eq!(features.mean_occurrences, 2.0);
+}
+
+#[test]
+fn parse_compressed() {
+    let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(CNF.as_bytes()).unwrap();
+    let compressed = TempPath::with_content("compressed.cnf.bz2", &encoder.finish().unwrap());
+    let plain = TempPath::with_content("reference.cnf", CNF.as_bytes());
+
+    assert_eq!(parse(&compressed).unwrap(), parse(&plain).unwrap());
+}
+
+#[test]
+fn parse_requires_header() {
+    let path = TempPath::with_content("headerless.cnf", b"1 -2 0\n");
+
+    assert!(matches!(parse(&path), Err(DimacsError::InvalidHeader)));
+}
@@ -0,0 +1,179 @@
+use super::dimacs::{parse, DimacsError};
+use bzip2::bufread::BzDecoder;
+use cowstr::CowStr;
+use flate2::bufread::GzDecoder;
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, remove_file, rename, File},
+    io::{self, BufRead, BufReader, BufWriter, ErrorKind, IntoInnerError, Read},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+use xz2::{bufread::XzDecoder, stream::Stream};
+
+const XZ_MAGIC: [u8; 6] = [0xFD, b'7', b'z', b'X', b'Z', 0x00];
+const LZMA_MAGIC: [u8; 3] = [0x5D, 0x00, 0x00];
+const GZ_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const BZ2_MAGIC: [u8; 3] = [b'B', b'Z', b'h'];
+
+#[derive(Debug, Error)]
+pub enum GBDError {
+    #[error("Request to GBD server failed")]
+    Request(#[from] Box<ureq::Error>),
+    #[error("Failed to write instance")]
+    IO(#[from] io::Error),
+    #[error("Invalid GBD hash: {0}")]
+    InvalidHash(String),
+    #[error("Failed to parse instance")]
+    Dimacs(#[from] DimacsError),
+    #[error("Downloaded instance {expected} has the hash {found}")]
+    HashMismatch { expected: String, found: String },
+}
+
+/// wrap `reader` in a decoder matching the magic bytes of its content (xz, lzma, gzip or bzip2)
+/// uncompressed content is passed through as is
+pub fn decompress<'a, R: BufRead + 'a>(mut reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    let magic = reader.fill_buf()?;
+
+    if magic.starts_with(&XZ_MAGIC) || magic.starts_with(&LZMA_MAGIC) {
+        let stream = Stream::new_auto_decoder(u64::MAX, 0)
+            .map_err(|error| io::Error::new(ErrorKind::Other, error))?;
+
+        Ok(Box::new(XzDecoder::new_stream(reader, stream)))
+    } else if magic.starts_with(&GZ_MAGIC) {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else if magic.starts_with(&BZ2_MAGIC) {
+        Ok(Box::new(BzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// GBD hashes are hex encoded MD5 sums, anything else is rejected to keep paths in `tmp_dir`
+fn validate_hash(hash: &str) -> Result<(), GBDError> {
+    if hash.len() == 32 && hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(GBDError::InvalidHash(hash.to_owned()))
+    }
+}
+
+/// resolve a GBD query to the hashes of all matching instances
+///
+/// The server answers with one instance URL per line, the hash is the last path segment.
+fn query_hashes(server: &str, query: &str) -> Result<Vec<String>, GBDError> {
+    let body = ureq::get(&format!("{server}/getinstances"))
+        .query("query", query)
+        .call()
+        .map_err(Box::new)?
+        .into_string()?;
+
+    Ok(body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| line.rsplit('/').next())
+        .map(str::to_owned)
+        .collect())
+}
+
+/// download and decompress a single instance into `path`
+fn download(server: &str, hash: &str, path: &Path) -> Result<(), GBDError> {
+    // write to a partial file first to avoid caching incomplete downloads
+    let partial = path.with_extension("cnf.part");
+
+    if let Err(error) = download_partial(server, hash, &partial) {
+        if let Err(error) = remove_file(&partial) {
+            debug!(path = ?partial, error = ?error, "Failed to remove partial download");
+        }
+
+        return Err(error);
+    }
+
+    rename(&partial, path)?;
+
+    Ok(())
+}
+
+/// download and decompress a single instance into `partial` and verify its hash
+fn download_partial(server: &str, hash: &str, partial: &Path) -> Result<(), GBDError> {
+    let response = ureq::get(&format!("{server}/file/{hash}"))
+        .call()
+        .map_err(Box::new)?;
+
+    let mut reader = decompress(BufReader::new(response.into_reader()))?;
+    let mut writer = BufWriter::new(File::create(partial)?);
+
+    io::copy(&mut reader, &mut writer)?;
+    // dropping the writer would ignore errors of the last write
+    writer
+        .into_inner()
+        .map_err(IntoInnerError::into_error)?
+        .sync_all()?;
+
+    // a corrupted download would be reused from the cache otherwise
+    let found = parse(partial)?.hash;
+
+    if !found.eq_ignore_ascii_case(hash) {
+        return Err(GBDError::HashMismatch {
+            expected: hash.to_owned(),
+            found,
+        });
+    }
+
+    Ok(())
+}
+
+/// Collect all instances selected by `hashes` and `query` from a GBD compatible server
+///
+/// Instances are stored as `{hash}.cnf` in `tmp_dir`, existing files are reused.
+pub fn load(
+    server: &http::Uri,
+    tmp_dir: &Path,
+    hashes: &[CowStr],
+    query: Option<&CowStr>,
+) -> Result<Vec<PathBuf>, GBDError> {
+    let server = server.to_string();
+    let server = server.trim_end_matches('/');
+
+    create_dir_all(tmp_dir)?;
+
+    let mut selected = hashes
+        .iter()
+        .map(|hash| hash.to_string())
+        .collect::<BTreeSet<String>>();
+
+    if let Some(query) = query {
+        let found = query_hashes(server, query.as_str())?;
+        info!(query = %query, "GBD query selected {} instances", found.len());
+
+        selected.extend(found);
+    }
+
+    let mut paths = Vec::with_capacity(selected.len());
+
+    for hash in selected {
+        validate_hash(&hash)?;
+
+        let path = tmp_dir.join(format!("{hash}.cnf"));
+
+        if path.is_file() {
+            debug!(hash = %hash, path = ?path, "Reusing cached instance");
+        } else {
+            debug!(hash = %hash, path = ?path, "Downloading instance");
+
+            if let Err(error) = download(server, &hash, &path) {
+                warn!(hash = %hash, error = ?error, "Failed to download instance: {error}");
+
+                return Err(error);
+            }
+        }
+
+        paths.push(path);
+    }
+
+    info!("Collected {} instances from {server}", paths.len());
+
+    Ok(paths)
+}
@@ -0,0 +1,130 @@
+use super::gbd::{decompress, load, GBDError};
+use crate::test_util::TempPath;
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    fs::{read_dir, read_to_string},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+};
+use xz2::write::XzEncoder;
+
+// md5 of "1 -2 0\n2 0\n"
+const HASH: &str = "4451bc9a106a7dc569485408faeeea76";
+const CNF: &str = "p cnf 2 2\n1 -2 0\n2 0\n";
+
+fn gzip(content: &str) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content.as_bytes()).unwrap();
+
+    encoder.finish().unwrap()
+}
+
+/// minimal stand-in for a GBD server that answers `requests` requests and returns their paths
+fn serve(requests: usize) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let server = address.clone();
+
+    let handle = thread::spawn(move || {
+        let mut paths = Vec::new();
+
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+
+            // skip all headers
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+
+            let path = request.split_whitespace().nth(1).unwrap().to_owned();
+            let body = if path.starts_with("/getinstances") {
+                format!("{server}/file/{HASH}\n").into_bytes()
+            } else {
+                gzip(CNF)
+            };
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+            paths.push(path);
+        }
+
+        paths
+    });
+
+    (address, handle)
+}
+
+#[test]
+pub fn gbd_download_and_cache() {
+    let tmp_dir = TempPath::new("gbd-cache");
+    let (address, handle) = serve(2);
+    let server: http::Uri = address.parse().unwrap();
+
+    let paths = load(&server, &tmp_dir, &[], Some(&"family=test".into())).unwrap();
+    let requests = handle.join().unwrap();
+
+    assert_eq!(paths, vec![tmp_dir.join(format!("{HASH}.cnf"))]);
+    assert!(requests[0].starts_with("/getinstances?query=family"));
+    assert_eq!(requests[1], format!("/file/{HASH}"));
+    assert_eq!(read_to_string(&paths[0]).unwrap(), CNF);
+
+    // the server is gone, a second load has to be served from the cache
+    let cached = load(&server, &tmp_dir, &[HASH.into()], None).unwrap();
+    assert_eq!(cached, paths);
+}
+
+#[test]
+pub fn gbd_rejects_hash_mismatch() {
+    let tmp_dir = TempPath::new("gbd-mismatch");
+    let (address, handle) = serve(1);
+    let server: http::Uri = address.parse().unwrap();
+    let hash = "0123456789abcdef0123456789abcdef";
+
+    assert!(matches!(
+        load(&server, &tmp_dir, &[hash.into()], None),
+        Err(GBDError::HashMismatch { .. })
+    ));
+    handle.join().unwrap();
+
+    // neither the instance nor the partial download may be left in the cache
+    assert_eq!(read_dir(&tmp_dir).unwrap().count(), 0);
+}
+
+#[test]
+pub fn gbd_rejects_invalid_hash() {
+    let tmp_dir = TempPath::new("gbd-invalid");
+    let server: http::Uri = "http://127.0.0.1:1".parse().unwrap();
+
+    assert!(load(&server, &tmp_dir, &["../../etc/passwd".into()], None).is_err());
+}
+
+#[test]
+pub fn decompress_by_magic() {
+    let mut encoder = XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(CNF.as_bytes()).unwrap();
+    let xz = encoder.finish().unwrap();
+
+    for input in [xz, gzip(CNF), CNF.as_bytes().to_vec()] {
+        let mut output = String::new();
+        decompress(input.as_slice())
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+
+        assert_eq!(output, CNF);
+    }
+}
@@ -1,8 +1,8 @@
 #[cfg(feature = "distributed")]
 use crate::distributed::{util::prepend_hostname, SynchronizationTypes};
 use crate::{
-    collector::{Collector, CollectorMap},
-    database::ConnectionError,
+    collector::{gbd::GBDError, Collector, CollectorMap},
+    database::{ConnectionError, MetricSchema, TestMetrics},
     executors::ExecutorError,
     ingest::{IngestorMap, Ingestors},
 };
@@ -14,11 +14,17 @@ use std::{
     fs::File,
     io::{BufReader, Error},
     os::unix::fs::MetadataExt,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::exit,
 };
+use sweep::{Assignment, SweepConfig};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
+use tracing_unwrap::ResultExt;
+
+pub mod sweep;
+#[cfg(test)]
+mod sweep_test;
 
 // check if a file is executable
 pub fn check_executable(path: &PathBuf) -> bool {
@@ -48,6 +54,8 @@ pub enum ConfigErrors {
     MetadataNotFound(#[from] Error),
     #[error("Database Connection failed")]
     DatabaseError(#[from] ConnectionError),
+    #[error("Failed to collect tests from GBD")]
+    GBDError(#[from] GBDError),
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -68,6 +76,10 @@ pub struct SolverConfig {
     #[serde(default)]
     pub delayed: bool,
 
+    // time in ms running solvers may continue after SIGINT or SIGTERM before they are killed
+    #[serde(default = "default_shutdown_grace_period")]
+    pub shutdown_grace_period: u32,
+
     #[serde(alias = "logs", default)]
     pub tracing: TracingConfig,
 }
@@ -90,6 +102,9 @@ impl Default for TracingConfig {
 pub struct DatabaseConfig {
     #[serde(default)]
     pub delayed: bool,
+    // write-ahead spool of delayed runs, defaults to the database path with a `.spool` suffix
+    #[serde(default)]
+    pub spool: Option<PathBuf>,
     pub batched: Option<BatchConfig>,
 
     #[cfg_attr(any(feature = "duckdb", feature = "duckdb"), serde(default))]
@@ -146,6 +161,29 @@ pub struct ExecIngestConfig {
     #[serde(default)]
     pub params: CowStr,
     pub timeout: u64,
+    // solver specific metrics reported in addition to the common ones, e.g., `decisions: Int`
+    #[serde(default)]
+    pub metrics: MetricSchema,
+}
+
+impl IngestorConfig {
+    /// metrics declared by the ingestor, the null ingestor doesn't support any
+    pub fn metrics(&self) -> MetricSchema {
+        match self {
+            Self::Exec(config) => config.metrics.clone(),
+            Self::Null => MetricSchema::new(),
+        }
+    }
+}
+
+/// check if `name` can be used in table and column names, they are quoted as they may be keywords
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -185,6 +223,12 @@ pub enum CollectorConfig {
         server: http::Uri,
         #[serde(default)]
         tmp_dir: Option<PathBuf>,
+        // GBD hashes of the selected instances
+        #[serde(default)]
+        hashes: Vec<CowStr>,
+        // GBD query that selects instances, merged with `hashes`
+        #[serde(default)]
+        query: Option<CowStr>,
     },
 }
 
@@ -192,6 +236,9 @@ pub enum CollectorConfig {
 #[serde(deny_unknown_fields)]
 pub struct TestSet {
     pub timeout: u32,
+    // time in ms between SIGTERM and SIGKILL when a run times out
+    #[serde(default = "default_grace_period")]
+    pub grace_period: u32,
     pub collector: CollectorConfig,
     #[serde(default = "default_iter_number")]
     pub iterations: usize,
@@ -199,6 +246,9 @@ pub struct TestSet {
     pub solvers: Vec<CowStr>,
     #[serde(default)]
     pub params: Vec<CowStr>,
+    // memory limit in MiB for all solvers in this set
+    #[serde(default)]
+    pub memory_limit: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -208,18 +258,119 @@ pub struct Solver {
     #[serde(default)]
     pub params: Vec<CowStr>,
     pub ingest: CowStr,
+    // memory limit in MiB for this solver
+    #[serde(default)]
+    pub memory_limit: Option<u64>,
+    // maximum captured output in MiB per stream, the remaining output is discarded
+    #[serde(default = "default_output_limit")]
+    pub output_limit: u64,
+    // verification of SAT and UNSAT answers
+    #[serde(default)]
+    pub verify: Option<VerifyConfig>,
+    // parameter space, the solver is replaced by a variant per configuration during preflight
+    #[serde(default)]
+    pub sweep: Option<SweepConfig>,
+    // parameter values of a variant, set by the expansion of the parameter space
+    #[serde(skip)]
+    pub assignment: Option<Assignment>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+/// Configuration for checking the answers of a solver
+pub struct VerifyConfig {
+    // check the model printed in `v ...` lines against the test file for SAT answers
+    #[serde(default)]
+    pub model: bool,
+    // check the proof written by the solver for UNSAT answers
+    pub proof: Option<ProofConfig>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ProofConfig {
+    // executed as <checker> <params> <test file> <proof file>, e.g., drat-trim
+    pub checker: PathBuf,
+    #[serde(default)]
+    pub params: Vec<CowStr>,
+    // timeout in ms for the proof checker
+    pub timeout: u32,
+}
+
+impl DatabaseConfig {
+    /// path of the spool of delayed runs
+    pub fn spool_path(&self) -> PathBuf {
+        if let Some(spool) = &self.spool {
+            return spool.clone();
+        }
+
+        match &self.connection {
+            #[cfg(feature = "duckdb")]
+            ConnectionConfig::DuckDB { path } => spool_suffix(path),
+            #[cfg(feature = "rusqlite")]
+            ConnectionConfig::SQLite { path } => spool_suffix(path),
+            #[cfg(feature = "clickhouse")]
+            ConnectionConfig::ClickHouse { database, .. } => {
+                PathBuf::from(format!("{database}.spool"))
+            }
+        }
+    }
+}
+
+/// convert a limit in MiB to bytes, `None` if it overflows
+fn mib_to_bytes(limit: u64) -> Option<u64> {
+    limit.checked_mul(1024 * 1024)
+}
+
+fn spool_suffix(path: &Path) -> PathBuf {
+    let mut spool = path.as_os_str().to_owned();
+    spool.push(".spool");
+
+    PathBuf::from(spool)
+}
+
+impl ConnectionConfig {
+    /// create a copy of a file based connection that points to `path`
+    pub fn with_path(&self, path: PathBuf) -> Option<Self> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB { .. } => Some(Self::DuckDB { path }),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite { .. } => Some(Self::SQLite { path }),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse { .. } => None,
+        }
+    }
 }
 
 impl Solver {
     pub fn get_params(&self) -> String {
         self.params.iter().join(" ")
     }
+
+    /// parameter values of a swept variant as JSON object
+    pub fn get_assignment(&self) -> Option<String> {
+        self.assignment
+            .as_ref()
+            .map(|assignment| serde_json::to_string(assignment).unwrap_or_log())
+    }
 }
 
 impl TestSet {
     pub fn get_params(&self) -> String {
         self.params.iter().join(" ")
     }
+
+    /// effective memory limit in bytes for `solver`, the stricter limit wins if both are set
+    ///
+    /// Limits that overflow in bytes are rejected by the preflight checks and ignored here.
+    pub fn memory_limit(&self, solver: &Solver) -> Option<u64> {
+        match (self.memory_limit, solver.memory_limit) {
+            (Some(set), Some(solver)) => Some(set.min(solver)),
+            (set, solver) => set.or(solver),
+        }
+        .and_then(mib_to_bytes)
+    }
 }
 
 impl SolverConfig {
@@ -295,11 +446,94 @@ impl SolverConfig {
         Ok(collectors)
     }
 
+    /// replace solvers with a parameter space by their variants, also in the test sets
+    ///
+    /// Called by `preflight_checks`, the returned flag is set if any parameter space is invalid.
+    pub fn expand_sweeps(&mut self) -> bool {
+        let mut contains_error = false;
+        let mut solvers = BTreeMap::new();
+        let mut variants = BTreeMap::<CowStr, Vec<CowStr>>::new();
+
+        for (name, solver) in std::mem::take(&mut self.solvers) {
+            let expanded = match sweep::expand(&name, &solver) {
+                Ok(expanded) if solver.sweep.is_some() => {
+                    info!(solver = %name, "Expanded solvers.{name} into {} variants", expanded.len());
+                    variants.insert(
+                        name.clone(),
+                        expanded
+                            .iter()
+                            .map(|(variant, _)| variant.clone())
+                            .collect(),
+                    );
+
+                    expanded
+                }
+                Ok(expanded) => expanded,
+                Err(error) => {
+                    error!(error = ?error, "solvers.{name}.sweep is invalid: {error}");
+                    contains_error = true;
+
+                    vec![(name, solver)]
+                }
+            };
+
+            for (name, solver) in expanded {
+                if solvers.contains_key(&name) {
+                    error!("solvers.{name} is defined twice, a variant of a swept solver has the same name");
+                    contains_error = true;
+                }
+
+                solvers.insert(name, solver);
+            }
+        }
+
+        self.solvers = solvers;
+
+        for set in self.tests.values_mut() {
+            set.solvers = set
+                .solvers
+                .drain(..)
+                .flat_map(|solver| {
+                    variants
+                        .get(&solver)
+                        .cloned()
+                        .unwrap_or_else(|| vec![solver])
+                })
+                .collect();
+        }
+
+        contains_error
+    }
+
+    /// make the database path host specific if the work is coordinated via the filesystem
+    ///
+    /// This is part of `preflight_checks` and has to be applied by all commands that write runs of
+    /// a benchmark.
+    pub fn host_specific_database(&mut self) {
+        #[cfg(feature = "distributed")]
+        if let ExecutorConfig::Distributed { synchronization } = &self.executor {
+            if let SynchronizationTypes::FileSystem { .. } = synchronization {
+                match &mut self.database.connection {
+                    ConnectionConfig::SQLite { path } | ConnectionConfig::DuckDB { path } => {
+                        if let Err(error) = prepend_hostname(path) {
+                            error!(error = ?error, "Failed to prepend hostname");
+                        } else {
+                            info!("Modified SQLite/DuckDB path to be host specific");
+                        }
+                    }
+                    // rejected in the preflight checks
+                    #[cfg(feature = "clickhouse")]
+                    ConnectionConfig::ClickHouse { .. } => (),
+                }
+            }
+        }
+    }
+
     pub fn preflight_checks(&mut self) -> bool {
         // TODO: Below is not performant nor clean, it should only work as a band aid solution
 
         // attempt to catch all errors instead of piece-by-piece to make debugging easier for users
-        let mut contains_error = false;
+        let mut contains_error = self.expand_sweeps();
 
         if self.solvers.is_empty() {
             error!("No solver was defined, unable to build a queue of tests");
@@ -312,6 +546,22 @@ impl SolverConfig {
                     error!("ingestor.{name}.executable must be a path to an executable file");
                     contains_error = true;
                 }
+
+                // metrics are stored in the columns of `{name}_metrics`
+                if !exec_config.metrics.is_empty() && !is_identifier(name) {
+                    error!("ingestor.{name} declares metrics, its name may only contain letters, digits and underscores");
+                    contains_error = true;
+                }
+
+                for metric in exec_config.metrics.keys() {
+                    if !is_identifier(metric) || metric.as_str() == "run" {
+                        error!("ingestor.{name}.metrics.{metric} is not a valid metric name, it may only contain letters, digits and underscores and can't be 'run'");
+                        contains_error = true;
+                    } else if TestMetrics::FIELDS.contains(&metric.as_str()) {
+                        error!("ingestor.{name}.metrics.{metric} is reported by all ingestors already, use a different name");
+                        contains_error = true;
+                    }
+                }
             }
         }
 
@@ -340,6 +590,43 @@ impl SolverConfig {
                 );
                 contains_error = true;
             }
+
+            if solver.memory_limit == Some(0) {
+                error!("solvers.{name}.memory_limit cannot be 0");
+                contains_error = true;
+            } else if solver
+                .memory_limit
+                .is_some_and(|limit| mib_to_bytes(limit).is_none())
+            {
+                error!("solvers.{name}.memory_limit exceeds the addressable memory");
+                contains_error = true;
+            }
+
+            if solver.output_limit == 0 {
+                error!("solvers.{name}.output_limit cannot be 0");
+                contains_error = true;
+            }
+
+            if let Some(ProofConfig {
+                checker, timeout, ..
+            }) = solver
+                .verify
+                .as_ref()
+                .and_then(|verify| verify.proof.as_ref())
+            {
+                if !checker.is_file() || check_executable(checker) {
+                    error!(
+                        "solvers.{name}.verify.proof.checker {} is not an executable file",
+                        checker.to_string_lossy()
+                    );
+                    contains_error = true;
+                }
+
+                if *timeout == 0 {
+                    error!("solvers.{name}.verify.proof.timeout cannot be 0");
+                    contains_error = true;
+                }
+            }
         }
 
         for (test, value) in self.tests.iter() {
@@ -373,7 +660,13 @@ impl SolverConfig {
             }
 
             match &mut value.collector {
-                CollectorConfig::Grouped { .. } | CollectorConfig::GDB { .. } => (),
+                CollectorConfig::Grouped { .. } => (),
+                CollectorConfig::GDB { hashes, query, .. } => {
+                    if hashes.is_empty() && query.is_none() {
+                        error!("Test {test} contains neither 'hashes' nor 'query' a test can't be a NOP");
+                        contains_error = true;
+                    }
+                }
                 CollectorConfig::Glob {
                     glob: _,
                     path,
@@ -395,31 +688,59 @@ impl SolverConfig {
                 }
             }
 
+            if value.memory_limit == Some(0) {
+                error!("Test {test}.memory_limit cannot be 0");
+                contains_error = true;
+            } else if value
+                .memory_limit
+                .is_some_and(|limit| mib_to_bytes(limit).is_none())
+            {
+                error!("Test {test}.memory_limit exceeds the addressable memory");
+                contains_error = true;
+            }
+
             if value.timeout == 0 {
                 error!("Test {test}.timeout cannot 0. This will lead to problems with evaluating some metrics.");
                 contains_error = true;
             }
         }
 
-        #[cfg(feature = "distributed")]
-        if let ExecutorConfig::Distributed { synchronization } = &self.executor {
-            if let SynchronizationTypes::FileSystem { .. } = synchronization {
-                match &mut self.database.connection {
-                    ConnectionConfig::SQLite { path } | ConnectionConfig::DuckDB { path } => {
-                        if let Err(error) = prepend_hostname(path) {
-                            error!(error = ?error, "Failed to prepend hostname");
-                        } else {
-                            info!("Modified SQLite/DuckDB path to be host specific");
-                        }
-                    }
-                }
-            }
+        self.host_specific_database();
+
+        // IDs are assigned by the runner, independent nodes writing to the same server would
+        // assign the same IDs to different runs
+        #[cfg(all(feature = "distributed", feature = "clickhouse"))]
+        if let (
+            ExecutorConfig::Distributed {
+                synchronization: SynchronizationTypes::FileSystem { .. },
+            },
+            ConnectionConfig::ClickHouse { .. },
+        ) = (&self.executor, &self.database.connection)
+        {
+            error!("ClickHouse can't be shared by the nodes of a file system synchronized executor, use MPI or a host specific SQLite/DuckDB database and merge them afterwards");
+            contains_error = true;
+        }
+
+        if let Some(BatchConfig {
+            timeout: Some(0), ..
+        }) = self.database.batched
+        {
+            error!("database.batched.timeout cannot be 0, omit it to only flush full batches");
+            contains_error = true;
         }
 
         if self.database.delayed && self.database.batched.is_some() {
             warn!("Enabling both database.delayed and database.batched is not recommended");
         }
 
+        if self.database.delayed && self.database.spool_path().exists() {
+            error!(
+                "Found the spool of a previous run, recover it with `satan-runner recover {}` or remove it",
+                self.database.spool_path().to_string_lossy()
+            );
+            contains_error = true;
+        }
+
         match &self.database.connection {
             #[cfg(feature = "rusqlite")]
             ConnectionConfig::SQLite { path } => {
@@ -455,13 +776,20 @@ impl SolverConfig {
             } => {
                 if (user.is_some() && password.is_none()) || (user.is_none() && password.is_some())
                 {
-                    error!("database.username: Either neither or both user and password need to be specified")
+                    error!("database.username: Either neither or both user and password need to be specified");
+                    contains_error = true;
                 }
 
-                #[cfg(feature = "clickhouse-lz4")]
+                #[cfg(not(feature = "clickhouse-lz4"))]
                 if lz4.is_some() || lz4hc.is_some() {
                     warn!("This binary was compiled without clickhouse compression support, the settings will be ignored");
                 }
+
+                #[cfg(feature = "clickhouse-lz4")]
+                if lz4hc.map_or(false, |level| !(1..=12).contains(&level)) {
+                    error!("database.lz4hc: The compression level needs to be between 1 and 12");
+                    contains_error = true;
+                }
             }
         }
 
@@ -483,6 +811,18 @@ fn default_iter_number() -> usize {
     1
 }
 
+fn default_grace_period() -> u32 {
+    1000
+}
+
+fn default_shutdown_grace_period() -> u32 {
+    30000
+}
+
+fn default_output_limit() -> u64 {
+    64
+}
+
 #[cfg(any(feature = "rusqlite", feature = "duckdb"))]
 impl Default for ConnectionConfig {
     #[cfg(feature = "rusqlite")]
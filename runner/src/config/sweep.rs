@@ -0,0 +1,294 @@
+use super::Solver;
+use cowstr::CowStr;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+};
+use thiserror::Error;
+
+/// Largest number of configurations a solver is expanded into
+pub const MAX_CONFIGURATIONS: u64 = 10_000;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SweepError {
+    #[error("Parameter {0} has no values")]
+    Empty(CowStr),
+    #[error("Parameter {0} needs a positive step and an end that isn't below its start")]
+    InvalidRange(CowStr),
+    #[error("Parameter space contains more than {MAX_CONFIGURATIONS} configurations")]
+    TooLarge,
+    #[error("At least one configuration has to be sampled")]
+    EmptySample,
+}
+
+/// Value of a swept parameter
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ParameterValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(CowStr),
+}
+
+/// Values of the swept parameters of a solver variant
+pub type Assignment = BTreeMap<CowStr, ParameterValue>;
+
+/// Values a parameter is swept over
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum ParameterSpace {
+    Values(Vec<ParameterValue>),
+    // start and end are inclusive, ranges of integers produce integer values
+    Range {
+        start: f64,
+        end: f64,
+        #[serde(default = "default_step")]
+        step: f64,
+    },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+/// Parameter space of a solver, each configuration becomes a solver variant
+pub struct SweepConfig {
+    // the cartesian product of all parameters is swept
+    pub parameters: BTreeMap<CowStr, ParameterSpace>,
+    // parameters appended to `params` per swept parameter, `{name}` and `{value}` are replaced
+    #[serde(default = "default_format")]
+    pub format: Vec<CowStr>,
+    // number of configurations drawn from the cartesian product (default: all)
+    #[serde(default)]
+    pub sample: Option<u64>,
+    // seed of the sample, the same seed always draws the same configurations
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl ParameterSpace {
+    /// number of values of the parameter `name`
+    fn len(&self, name: &CowStr) -> Result<u64, SweepError> {
+        let len = match self {
+            Self::Values(values) => values.len() as u64,
+            Self::Range { start, end, step } => {
+                let finite = start.is_finite() && end.is_finite() && step.is_finite();
+
+                if !finite || *step <= 0.0 || end < start {
+                    return Err(SweepError::InvalidRange(name.clone()));
+                }
+
+                // tolerate rounding errors of fractional steps at the end of the range
+                let count = ((end - start) / step + 1e-9).floor() + 1.0;
+
+                // such ranges can't be indexed, their values aren't distinct as floats anyway
+                if count >= u64::MAX as f64 {
+                    return Err(SweepError::TooLarge);
+                }
+
+                count as u64
+            }
+        };
+
+        if len == 0 {
+            return Err(SweepError::Empty(name.clone()));
+        }
+
+        Ok(len)
+    }
+
+    /// value at `index`, ranges are never materialized
+    fn value(&self, index: u64) -> ParameterValue {
+        match self {
+            Self::Values(values) => values[index as usize].clone(),
+            Self::Range { start, step, .. } => {
+                let value = start + index as f64 * step;
+
+                if start.fract() == 0.0 && step.fract() == 0.0 {
+                    ParameterValue::Int(value as i64)
+                } else {
+                    // hide rounding errors in the names of the variants
+                    ParameterValue::Float((value * 1e9).round() / 1e9)
+                }
+            }
+        }
+    }
+}
+
+impl SweepConfig {
+    /// All sampled assignments of the parameter space, ordered by their position in the product
+    ///
+    /// Only the produced configurations are limited by `MAX_CONFIGURATIONS`, a sample may be
+    /// drawn from a parameter space of any size.
+    pub fn assignments(&self) -> Result<Vec<Assignment>, SweepError> {
+        let lengths = self
+            .parameters
+            .iter()
+            .map(|(name, space)| space.len(name))
+            .collect::<Result<Vec<_>, SweepError>>()?;
+
+        let size = lengths
+            .iter()
+            .try_fold(1u64, |size, length| size.checked_mul(*length));
+
+        let configurations: Vec<Vec<u64>> = match (size, self.sample) {
+            (_, Some(0)) => return Err(SweepError::EmptySample),
+            (_, Some(sample)) if sample > MAX_CONFIGURATIONS => return Err(SweepError::TooLarge),
+            (Some(size), Some(sample)) if sample < size => sample_indices(size, sample, self.seed)
+                .into_iter()
+                .map(|index| digits(&lengths, index))
+                .collect(),
+            (Some(size), _) if size <= MAX_CONFIGURATIONS => {
+                (0..size).map(|index| digits(&lengths, index)).collect()
+            }
+            // the product doesn't fit into an index, but the sample is tiny in comparison
+            (None, Some(sample)) => sample_digits(&lengths, sample, self.seed)
+                .into_iter()
+                .collect(),
+            _ => return Err(SweepError::TooLarge),
+        };
+
+        Ok(configurations
+            .into_iter()
+            .map(|digits| {
+                self.parameters
+                    .iter()
+                    .zip(digits)
+                    .map(|((name, space), index)| (name.clone(), space.value(index)))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Parameters passed to the solver for `assignment`
+    pub fn params(&self, assignment: &Assignment) -> Vec<CowStr> {
+        assignment
+            .iter()
+            .flat_map(|(name, value)| {
+                self.format.iter().map(move |format| {
+                    CowStr::from(
+                        format
+                            .replace("{name}", name.as_str())
+                            .replace("{value}", &value.to_string()),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Expand the solver `name` into a variant per configuration of its parameter space
+///
+/// Variants are named `name[parameter=value,...]` and append the parameters of their assignment
+/// to the parameters of the solver.
+pub fn expand(name: &CowStr, solver: &Solver) -> Result<Vec<(CowStr, Solver)>, SweepError> {
+    let Some(sweep) = &solver.sweep else {
+        return Ok(vec![(name.clone(), solver.clone())]);
+    };
+
+    Ok(sweep
+        .assignments()?
+        .into_iter()
+        .map(|assignment| {
+            let mut variant = solver.clone();
+
+            variant.params.extend(sweep.params(&assignment));
+            variant.sweep = None;
+
+            let name = CowStr::from(format!(
+                "{name}[{}]",
+                assignment
+                    .iter()
+                    .map(|(parameter, value)| format!("{parameter}={value}"))
+                    .join(",")
+            ));
+            variant.assignment = Some(assignment);
+
+            (name, variant)
+        })
+        .collect())
+}
+
+/// Indices of the values of every parameter of the configuration at `index` in the product
+fn digits(lengths: &[u64], mut index: u64) -> Vec<u64> {
+    let mut digits = vec![0; lengths.len()];
+
+    // the last parameter changes fastest
+    for (digit, length) in digits.iter_mut().zip(lengths).rev() {
+        *digit = index % length;
+        index /= length;
+    }
+
+    digits
+}
+
+/// Draw `count` distinct configurations of a product too large to be indexed, value by value
+///
+/// The values of a uniformly drawn configuration are independent and uniform as well. The
+/// product has more than `u64::MAX` configurations, so duplicates are practically impossible.
+fn sample_digits(lengths: &[u64], count: u64, seed: u64) -> BTreeSet<Vec<u64>> {
+    let mut random = SplitMix64(seed);
+    let mut configurations = BTreeSet::new();
+
+    while (configurations.len() as u64) < count {
+        configurations.insert(
+            lengths
+                .iter()
+                .map(|length| random.next_u64() % length)
+                .collect(),
+        );
+    }
+
+    configurations
+}
+
+/// Draw `count` distinct indices below `size` with Floyd's algorithm
+fn sample_indices(size: u64, count: u64, seed: u64) -> BTreeSet<u64> {
+    let mut random = SplitMix64(seed);
+    let mut indices = BTreeSet::new();
+
+    for upper in size - count..size {
+        let index = random.next_u64() % (upper + 1);
+
+        if !indices.insert(index) {
+            indices.insert(upper);
+        }
+    }
+
+    indices
+}
+
+/// SplitMix64 generator, sufficient for reproducible samples of configurations and bootstraps
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+        z ^ (z >> 31)
+    }
+}
+
+fn default_step() -> f64 {
+    1.0
+}
+
+fn default_format() -> Vec<CowStr> {
+    vec![CowStr::from("--{name}={value}")]
+}
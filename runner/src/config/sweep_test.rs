@@ -0,0 +1,173 @@
+use super::{
+    sweep::{expand, ParameterValue, SweepError},
+    Solver,
+};
+use cowstr::CowStr;
+use std::collections::BTreeSet;
+
+fn solver(sweep: &str) -> Solver {
+    serde_yaml::from_str(&format!(
+        "exec: /usr/bin/cadical
+params: [-q]
+ingest: cadical
+sweep:
+{sweep}"
+    ))
+    .unwrap()
+}
+
+fn params(solver: &Solver) -> Vec<&str> {
+    solver.params.iter().map(CowStr::as_str).collect()
+}
+
+#[test]
+fn expand_product() {
+    let solver = solver(
+        "  parameters:
+    elim: [true, false]
+    restartint: {start: 10, end: 30, step: 10}",
+    );
+    let variants = expand(&CowStr::from("cadical"), &solver).unwrap();
+
+    // the last parameter changes fastest
+    assert_eq!(variants.len(), 6);
+    assert_eq!(variants[0].0.as_str(), "cadical[elim=true,restartint=10]");
+    assert_eq!(variants[1].0.as_str(), "cadical[elim=true,restartint=20]");
+    assert_eq!(variants[5].0.as_str(), "cadical[elim=false,restartint=30]");
+
+    let (_, variant) = &variants[1];
+    assert_eq!(
+        params(variant),
+        vec!["-q", "--elim=true", "--restartint=20"]
+    );
+    assert!(variant.sweep.is_none());
+    assert_eq!(
+        variant.get_assignment().unwrap(),
+        r#"{"elim":true,"restartint":20}"#
+    );
+
+    // fractional steps produce floats including the end of the range
+    let solver = solver(
+        "  parameters:
+    decay: {start: 0.5, end: 0.7, step: 0.1}
+  format: ['--{name}', '{value}']",
+    );
+    let variants = expand(&CowStr::from("cadical"), &solver).unwrap();
+
+    assert_eq!(variants.len(), 3);
+    assert_eq!(params(&variants[0].1), vec!["-q", "--decay", "0.5"]);
+    assert_eq!(variants[2].0.as_str(), "cadical[decay=0.7]");
+    assert_eq!(
+        variants[2].1.assignment.as_ref().unwrap()["decay"],
+        ParameterValue::Float(0.7)
+    );
+}
+
+#[test]
+fn expand_sample() {
+    let sweep = "  parameters:
+    a: {start: 1, end: 100}
+    b: {start: 1, end: 100}
+  sample: 20
+  seed: 7";
+    let variants = expand(&CowStr::from("cadical"), &solver(sweep)).unwrap();
+    let names = variants
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    // samples are distinct and the same seed draws the same configurations
+    assert_eq!(variants.len(), 20);
+    assert_eq!(names.iter().collect::<BTreeSet<_>>().len(), 20);
+    assert_eq!(
+        expand(&CowStr::from("cadical"), &solver(sweep))
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        names
+    );
+}
+
+#[test]
+fn expand_sample_large() {
+    // a range beyond the limit is only sampled, never enumerated
+    let variants = expand(
+        &CowStr::from("cadical"),
+        &solver(
+            "  parameters:
+    a: {start: 1, end: 1000000000}
+  sample: 3",
+        ),
+    )
+    .unwrap();
+    assert_eq!(variants.len(), 3);
+
+    // the product doesn't fit into 64 bits
+    let variants = expand(
+        &CowStr::from("cadical"),
+        &solver(
+            "  parameters:
+    a: {start: 1, end: 10000000}
+    b: {start: 1, end: 10000000}
+    c: {start: 1, end: 10000000}
+  sample: 5
+  seed: 3",
+        ),
+    )
+    .unwrap();
+    let names = variants
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+
+    // distinct and in the order of the product
+    assert_eq!(names.len(), 5);
+    assert!(names.windows(2).all(|pair| {
+        let parse = |name: &str| {
+            name.trim_start_matches("cadical[")
+                .trim_end_matches(']')
+                .split(',')
+                .map(|value| value.split('=').nth(1).unwrap().parse::<u64>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        parse(pair[0]) < parse(pair[1])
+    }));
+}
+
+#[test]
+fn expand_invalid() {
+    let error = |sweep: &str| expand(&CowStr::from("cadical"), &solver(sweep)).unwrap_err();
+
+    assert_eq!(
+        error(
+            "  parameters:
+    a: {start: 3, end: 1}"
+        ),
+        SweepError::InvalidRange(CowStr::from("a"))
+    );
+    assert_eq!(
+        error(
+            "  parameters:
+    a: []"
+        ),
+        SweepError::Empty(CowStr::from("a"))
+    );
+    assert_eq!(
+        error(
+            "  parameters:
+    a: {start: 1, end: 1000}
+    b: {start: 1, end: 1000}"
+        ),
+        SweepError::TooLarge
+    );
+    assert_eq!(
+        error(
+            "  parameters:
+    a: [1, 2]
+  sample: 0"
+        ),
+        SweepError::EmptySample
+    );
+}
@@ -1,22 +1,44 @@
 pub mod batched;
+#[cfg(all(test, feature = "rusqlite"))]
+mod batched_test;
 #[cfg(feature = "clickhouse")]
 pub mod clickhouse;
+#[cfg(all(test, feature = "clickhouse"))]
+mod clickhouse_test;
 pub mod delayed;
 #[cfg(feature = "duckdb")]
 pub mod duckdb;
+pub mod export;
+#[cfg(test)]
+mod export_test;
+#[cfg(all(test, feature = "rusqlite"))]
+mod merge_test;
+pub mod spool;
+#[cfg(test)]
+mod spool_test;
 #[cfg(feature = "rusqlite")]
 pub mod sqlite;
+pub mod transfer;
+#[cfg(test)]
+mod transfer_test;
 pub mod util;
 
-use crate::config::{ConnectionConfig, DatabaseConfig, SolverConfig};
+use crate::{
+    collector::dimacs::InstanceFeatures,
+    config::{ConnectionConfig, DatabaseConfig, SolverConfig},
+};
 use cowstr::CowStr;
+use export::{ExportFilter, ExportRow};
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
-
-// MID TERM: add clickhouse
-// LONG TERM: add exporter (CSV) and migrator (duckdb <-> clickhouse)
+use tracing::error;
+use util::SchemaVersion;
 
 // Alias for all database IDs for benchmarks, solvers and testsets
 // This might be upped to an i64 if the demand ever arises
@@ -30,6 +52,40 @@ pub enum Satisfiability {
     Satisfiable = 1,
 }
 
+/// Result of checking the answer of a run against the test file
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone, Copy, Default)]
+#[repr(i8)]
+pub enum Verification {
+    // the model didn't satisfy the formula or the proof was rejected
+    Invalid = -1,
+    // verification is disabled or the answer was unknown
+    #[default]
+    Unchecked = 0,
+    Verified = 1,
+    // the answer couldn't be checked, e.g., due to a missing model or a failing proof checker
+    Error = 2,
+}
+
+/// Outcome of a single run as determined by the executor
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub enum RunStatus {
+    #[default]
+    Ok,
+    Timeout,
+    Memout,
+    // terminated by the signal
+    Signal(i32),
+    // exited with a code other than 0, 10 (SAT) or 20 (UNSAT)
+    ExitCode(i32),
+    IngestError,
+    SpawnError,
+    // stored by a newer runner or corrupted, it never counts as successful
+    Unknown {
+        kind: i8,
+        code: Option<i32>,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestMetrics {
     // TODO: figure out more relevant metrics, either compare between the outputs from the SAT
@@ -44,14 +100,78 @@ pub struct TestMetrics {
     pub conflict_literals: u32,
     pub number_of_variables: u32,
     pub number_of_clauses: u32,
+    // measured by the executor, independent of the ingestor
+    #[serde(default)]
+    pub resources: ResourceUsage,
+    #[serde(default)]
+    pub status: RunStatus,
+    #[serde(default)]
+    pub verification: Verification,
+    // solver specific metrics declared by the ingestor, stored in `{ingestor}_metrics`
+    #[serde(flatten, default)]
+    pub extra: Metrics,
 }
 
-#[derive(Debug)]
+/// Type of a metric declared by an ingestor
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MetricType {
+    Int,
+    Float,
+    String,
+}
+
+/// Value of a metric declared by an ingestor
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum MetricValue {
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// Declared metrics of an ingestor by their name
+pub type MetricSchema = BTreeMap<CowStr, MetricType>;
+pub type Metrics = BTreeMap<CowStr, MetricValue>;
+
+impl MetricValue {
+    /// convert the value into `metric_type`, integers are accepted as floats
+    pub fn coerce(self, metric_type: MetricType) -> Option<Self> {
+        match (self, metric_type) {
+            (Self::Int(value), MetricType::Float) => Some(Self::Float(value as f64)),
+            (value @ Self::Int(_), MetricType::Int)
+            | (value @ Self::Float(_), MetricType::Float)
+            | (value @ Self::String(_), MetricType::String) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Resource usage of a solver process as reported by wait4(2)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    // maximum resident set size in KiB
+    pub max_rss: u64,
+    // user and system CPU time in µs
+    pub user_time: u64,
+    pub system_time: u64,
+    pub voluntary_context_switches: u64,
+    pub involuntary_context_switches: u64,
+    pub minor_page_faults: u64,
+    pub major_page_faults: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsBundle {
     pub metrics: TestMetrics,
     pub solver: CowStr,
     pub test_set: CowStr,
     pub target: PathBuf,
+    // hash of the instance, if it could be parsed
+    #[serde(default)]
+    pub instance: Option<CowStr>,
+    // ID reserved by buffering connections, other runs are assigned an ID on insert
+    #[serde(default)]
+    pub id: Option<ID>,
 }
 
 #[derive(Debug, Error)]
@@ -62,8 +182,66 @@ pub enum ConnectionError {
     #[cfg_attr(feature = "rusqlite", error("SQLite adapter error"))]
     #[cfg(feature = "rusqlite")]
     SQLite(rusqlite::Error),
+    #[cfg_attr(feature = "clickhouse", error("ClickHouse adapter error"))]
+    #[cfg(feature = "clickhouse")]
+    ClickHouse(clickhouse::ClickHouseError),
     #[error("Invalid adapter configuration")]
     ConfigError,
+    #[error("Found reference to a missing entry in {0}")]
+    DanglingReference(String),
+    #[error("Failed to access spool")]
+    Spool(#[from] std::io::Error),
+    #[error("Failed to (de)serialize JSON")]
+    Json(#[from] serde_json::Error),
+    #[error("Spool doesn't start with a benchmark")]
+    InvalidSpool,
+    #[error("Database schema version {0} is newer than the supported version")]
+    UnsupportedSchema(SchemaVersion),
+    #[error("Database schema version {0} is outdated, upgrade it with `satan-runner migrate`")]
+    OutdatedSchema(SchemaVersion),
+    #[error("Connection was initialized already")]
+    AlreadyInitialized,
+}
+
+/// Number of completed runs per test set, solver and instance of a benchmark
+///
+/// Instances are identified by their hash, test files without a hash by their path, see
+/// `instance_key`.
+pub type CompletedRuns = BTreeMap<(CowStr, CowStr, String), usize>;
+
+/// Key of an instance in `CompletedRuns`
+///
+/// The hash survives moved and renamed test files, e.g., the prefixes of the filesystem
+/// coordinator, only test files without a hash fall back to their path.
+pub fn instance_key(instance: Option<&CowStr>, target: &Path) -> String {
+    match instance {
+        Some(hash) => hash.to_string(),
+        None => target.to_string_lossy().to_string(),
+    }
+}
+
+/// Run that answered differently than another run on the same instance of a benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictRow {
+    pub run: ID,
+    // hash of the instance or the path of test files without a hash
+    pub instance: String,
+    pub satisfiable: i8,
+}
+
+/// Summary of all entries that were merged from another database
+#[derive(Debug, Default, Clone)]
+pub struct MergeReport {
+    pub benchmarks: usize,
+    pub solvers_created: usize,
+    pub solvers_reused: usize,
+    pub test_sets_created: usize,
+    pub test_sets_reused: usize,
+    pub runs: usize,
+    pub instances: usize,
+    // rows of the metric tables of all ingestors
+    pub metrics: usize,
+    pub conflicts: usize,
 }
 
 #[derive(Debug)]
@@ -87,22 +265,150 @@ impl ConnectionAdapter {
         solver: CowStr,
         test_set: CowStr,
         target: &PathBuf,
+        instance: Option<CowStr>,
     ) -> Result<ID, ConnectionError> {
         match self {
             #[cfg(feature = "duckdb")]
             Self::DuckDB(shared_connection) => {
-                shared_connection.store(metrics, solver, test_set, target)
+                shared_connection.store(metrics, solver, test_set, target, instance)
             }
             Self::Batched(shared_connection) => {
-                shared_connection.store(metrics, solver, test_set, target)
+                shared_connection.store(metrics, solver, test_set, target, instance)
+            }
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => {
+                shared_connection.store(metrics, solver, test_set, target, instance)
+            }
+            Self::Delayed(delayed) => delayed.store(metrics, solver, test_set, target, instance),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => {
+                connection.store(metrics, solver, test_set, target, instance)
+            }
+        }
+    }
+
+    /// Insert the features of an instance, this is never delayed as every instance is only stored
+    /// once
+    pub fn store_instance(&self, features: &InstanceFeatures) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.store_instance(features),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.store_instance(features),
+            Self::Batched(shared_connection) => shared_connection.store_instance(features),
+            Self::Delayed(shared_connection) => shared_connection.store_instance(features),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.store_instance(features),
+        }
+    }
+
+    /// Count the completed runs of the current benchmark, this is used to resume a benchmark
+    ///
+    /// Runs that failed due to the runner (spawn and ingest errors) aren't counted and are repeated.
+    pub fn completed_runs(&self) -> Result<CompletedRuns, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.completed_runs(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.completed_runs(),
+            Self::Batched(shared_connection) => shared_connection.completed_runs(),
+            Self::Delayed(shared_connection) => shared_connection.completed_runs(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.completed_runs(),
+        }
+    }
+
+    /// Pass the runs matching `filter` to `f` one by one, e.g., to stream them into an export
+    pub fn export_each<E: From<ConnectionError>>(
+        &self,
+        filter: &ExportFilter,
+        f: impl FnMut(ExportRow) -> Result<(), E>,
+    ) -> Result<(), E> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.export_each(filter, f),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.export_each(filter, f),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse { .. } => Err(ConnectionError::ConfigError.into()),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Collect the runs matching `filter` for an analysis
+    pub fn export(&self, filter: &ExportFilter) -> Result<Vec<ExportRow>, ConnectionError> {
+        let mut rows = Vec::new();
+
+        self.export_each(filter, |row| {
+            rows.push(row);
+
+            Ok::<(), ConnectionError>(())
+        })?;
+
+        Ok(rows)
+    }
+
+    /// Replace the answer conflicts recorded for `benchmark` with `conflicts`
+    pub fn store_conflicts(
+        &self,
+        benchmark: ID,
+        conflicts: &[ConflictRow],
+    ) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => {
+                shared_connection.store_conflicts(benchmark, conflicts)
             }
             #[cfg(feature = "rusqlite")]
             Self::SQLite(shared_connection) => {
-                shared_connection.store(metrics, solver, test_set, target)
+                shared_connection.store_conflicts(benchmark, conflicts)
             }
-            Self::Delayed(delayed) => delayed.store(metrics, solver, test_set, target),
             #[cfg(feature = "clickhouse")]
-            Self::ClickHouse { .. } => todo!(),
+            Self::ClickHouse { .. } => Err(ConnectionError::ConfigError),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Reserve the ID of a run that is inserted later with `store_iter`, e.g., by buffering
+    /// connections
+    pub fn reserve_run_id(&self) -> Result<ID, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.reserve_run_id(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.reserve_run_id(),
+            Self::Batched(shared_connection) => shared_connection.reserve_run_id(),
+            Self::Delayed(shared_connection) => shared_connection.reserve_run_id(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.reserve_run_id(),
+        }
+    }
+
+    /// ID of the current benchmark, only valid after `init`
+    pub fn benchmark(&self) -> ID {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.benchmark(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.benchmark(),
+            Self::Batched(shared_connection) => shared_connection.benchmark(),
+            Self::Delayed(shared_connection) => shared_connection.benchmark(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.benchmark(),
+        }
+    }
+
+    /// Mark the current benchmark as interrupted, this is never delayed to survive failed flushes
+    pub fn mark_interrupted(&self) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.mark_interrupted(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.mark_interrupted(),
+            Self::Batched(shared_connection) => shared_connection.mark_interrupted(),
+            Self::Delayed(shared_connection) => shared_connection.mark_interrupted(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.mark_interrupted(),
         }
     }
 
@@ -116,10 +422,22 @@ impl ConnectionAdapter {
             Self::Batched(shared_connection) => shared_connection.close(),
             Self::Delayed(shared_connection) => shared_connection.close(),
             #[cfg(feature = "clickhouse")]
-            Self::ClickHouse { .. } => todo!(),
+            Self::ClickHouse(connection) => connection.close(),
         }
     }
 
+    /// close the connection after a benchmark, flagging it as interrupted first if necessary
+    pub fn finish(self, interrupted: bool) -> Result<(), ConnectionError> {
+        if interrupted {
+            if let Err(e) = self.mark_interrupted() {
+                error!(error = ?e, "Failed to mark benchmark as interrupted: {e}");
+            }
+        }
+
+        // the connection is closed regardless to flush buffered runs
+        self.close()
+    }
+
     /// Estabilish a connection without wrapped types
     pub fn load_connection(config: &DatabaseConfig) -> Result<Self, ConnectionError> {
         match config.connection {
@@ -132,7 +450,9 @@ impl ConnectionAdapter {
                 sqlite::SharedConnection::load(&config.connection).map(Self::SQLite)
             }
             #[cfg(feature = "clickhouse")]
-            DatabaseConfig::ClickHouse { .. } => todo!(),
+            ConnectionConfig::ClickHouse { .. } => {
+                clickhouse::CHConnection::load(&config.connection).map(Self::ClickHouse)
+            }
         }
     }
 
@@ -141,6 +461,7 @@ impl ConnectionAdapter {
         if config.delayed {
             Ok(Self::Delayed(delayed::DelayedConnection::load(
                 Self::load_connection(config)?,
+                config.spool_path(),
             )))
         } else if let Some(batched_config) = &config.batched {
             Ok(Self::Batched(batched::BatchedConnection::load(
@@ -168,7 +489,57 @@ impl ConnectionAdapter {
             Self::SQLite(shared_connection) => shared_connection.init(config, benchmark, comment),
             Self::Delayed(shared_connection) => shared_connection.init(config, benchmark, comment),
             #[cfg(feature = "clickhouse")]
-            Self::ClickHouse { .. } => todo!(),
+            Self::ClickHouse(connection) => connection.init(config, benchmark, comment),
+        }
+    }
+
+    /// Merge all entries of another database into this database
+    pub fn merge(&mut self, source: &ConnectionConfig) -> Result<MergeReport, ConnectionError> {
+        if let Self::Batched { .. } | Self::Delayed { .. } = self {
+            unreachable!()
+        }
+
+        let source = Self::load_connection(&DatabaseConfig {
+            delayed: false,
+            spool: None,
+            batched: None,
+            connection: source.clone(),
+        })?;
+        // merged databases are never written, older ones have to be upgraded by `migrate` first
+        source.check_schema()?;
+
+        let report = transfer::merge(&source, self)?;
+        source.close()?;
+
+        Ok(report)
+    }
+
+    /// Upgrade the schema of the database to the version of the runner
+    ///
+    /// Returns the schema versions before and after the upgrade.
+    pub fn migrate(&mut self) -> Result<(SchemaVersion, SchemaVersion), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.migrate(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.migrate(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse { .. } => Err(ConnectionError::ConfigError),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Fail unless the schema of the database is up to date, without upgrading it
+    pub fn check_schema(&self) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.check_schema(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.check_schema(),
+            // ClickHouse tables aren't versioned, every run applies the current schema
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse { .. } => Ok(()),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
         }
     }
 
@@ -181,12 +552,78 @@ impl ConnectionAdapter {
             Self::DuckDB(shared_connection) => shared_connection.store_iter(metrics),
             #[cfg(feature = "rusqlite")]
             Self::SQLite(shared_connection) => shared_connection.store_iter(metrics),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.store_iter(metrics),
             Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
         }
     }
 }
 
+impl RunStatus {
+    /// numeric representation that is stored in the `status` column
+    pub fn kind(&self) -> i8 {
+        match self {
+            Self::Ok => 0,
+            Self::Timeout => 1,
+            Self::Memout => 2,
+            Self::Signal(_) => 3,
+            Self::ExitCode(_) => 4,
+            Self::IngestError => 5,
+            Self::SpawnError => 6,
+            Self::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    /// signal number or exit code that is stored in the `status_code` column
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Self::Signal(code) | Self::ExitCode(code) => Some(*code),
+            Self::Unknown { code, .. } => *code,
+            _ => None,
+        }
+    }
+
+    /// create a status from its database representation
+    pub fn from_parts(kind: i8, code: Option<i32>) -> Self {
+        match kind {
+            0 => Self::Ok,
+            1 => Self::Timeout,
+            2 => Self::Memout,
+            3 => Self::Signal(code.unwrap_or_default()),
+            4 => Self::ExitCode(code.unwrap_or_default()),
+            5 => Self::IngestError,
+            6 => Self::SpawnError,
+            kind => Self::Unknown { kind, code },
+        }
+    }
+}
+
 impl TestMetrics {
+    /// names of all fields, declared metrics are flattened into the same object and can't reuse them
+    pub const FIELDS: [&'static str; 13] = [
+        "runtime",
+        "satisfiable",
+        "parse_time",
+        "memory_usage",
+        "restarts",
+        "conflicts",
+        "propagations",
+        "conflict_literals",
+        "number_of_variables",
+        "number_of_clauses",
+        "resources",
+        "status",
+        "verification",
+    ];
+
+    /// empty metrics for a run that didn't produce any usable output
+    pub fn with_status(status: RunStatus) -> Self {
+        Self {
+            status,
+            ..Self::failed()
+        }
+    }
+
     pub fn failed() -> Self {
         Self {
             runtime: 0,
@@ -199,6 +636,10 @@ impl TestMetrics {
             satisfiable: Satisfiability::Unknown,
             conflicts: 0,
             memory_usage: 0,
+            resources: ResourceUsage::default(),
+            status: RunStatus::Ok,
+            verification: Verification::Unchecked,
+            extra: Metrics::new(),
         }
     }
 }
@@ -1,23 +1,127 @@
-use super::{ConnectionAdapter, ConnectionError, MetricsBundle, TestMetrics, ID};
-use crate::config::{BatchConfig, SolverConfig};
+use super::{CompletedRuns, ConnectionAdapter, ConnectionError, MetricsBundle, TestMetrics, ID};
+use crate::{
+    collector::dimacs::InstanceFeatures,
+    config::{BatchConfig, SolverConfig},
+};
 use cowstr::CowStr;
 use parking_lot::FairMutex;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::PathBuf,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tracing::{debug, error};
 use tracing_unwrap::ResultExt;
 
 #[derive(Debug)]
 pub struct BatchedConnection {
-    connection: Box<ConnectionAdapter>,
-    buffer: Arc<FairMutex<Vec<MetricsBundle>>>,
+    connection: Arc<ConnectionAdapter>,
+    buffer: Arc<FairMutex<Batch>>,
     size: u32,
+    timeout: Option<Duration>,
+    // only running after `init` if a timeout is configured
+    flusher: Option<Flusher>,
+}
+
+/// Buffered runs and the time they were last written
+#[derive(Debug)]
+struct Batch {
+    bundles: Vec<MetricsBundle>,
+    flushed: Instant,
+}
+
+/// Background thread that flushes the buffer once the timeout elapsed since the last flush
+#[derive(Debug)]
+struct Flusher {
+    // dropping the sender stops the flusher
+    stop: Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl Batch {
+    /// store all buffered runs, they remain buffered and are retried by the next flush if the
+    /// insert fails
+    fn flush(&mut self, connection: &ConnectionAdapter) -> Result<(), ConnectionError> {
+        self.flushed = Instant::now();
+
+        if self.bundles.is_empty() {
+            return Ok(());
+        }
+
+        connection.store_iter(self.bundles.iter().cloned())?;
+        self.bundles.clear();
+
+        Ok(())
+    }
+}
+
+impl Flusher {
+    fn spawn(
+        timeout: Duration,
+        connection: Arc<ConnectionAdapter>,
+        buffer: Arc<FairMutex<Batch>>,
+    ) -> Self {
+        let (stop, receiver) = mpsc::channel::<()>();
+
+        let handle = thread::spawn(move || loop {
+            let remaining = timeout.saturating_sub(buffer.lock().flushed.elapsed());
+
+            match receiver.recv_timeout(remaining) {
+                Err(RecvTimeoutError::Timeout) => {
+                    let mut batch = buffer.lock();
+
+                    // a full batch may have been flushed by `store` in the meantime
+                    if batch.flushed.elapsed() < timeout {
+                        continue;
+                    }
+
+                    let count = batch.bundles.len();
+
+                    match batch.flush(&connection) {
+                        Ok(()) if count > 0 => {
+                            debug!("Flushed {count} buffered runs after timeout")
+                        }
+                        Ok(()) => (),
+                        Err(e) => error!(
+                            error = ?e,
+                            "Failed to flush {count} buffered runs, retrying after the timeout: {e}"
+                        ),
+                    }
+                }
+                // the remaining runs are flushed by `close`
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        });
+
+        Self { stop, handle }
+    }
+
+    fn stop(self) {
+        drop(self.stop);
+
+        if self.handle.join().is_err() {
+            error!("Batch flusher panicked");
+        }
+    }
 }
 
 impl BatchedConnection {
     pub fn load(config: &BatchConfig, connection: ConnectionAdapter) -> Self {
         Self {
-            buffer: Arc::new(FairMutex::new(Vec::new())),
+            buffer: Arc::new(FairMutex::new(Batch {
+                bundles: Vec::new(),
+                flushed: Instant::now(),
+            })),
             size: config.size,
-            connection: Box::new(connection),
+            timeout: config
+                .timeout
+                .map(|timeout| Duration::from_millis(timeout as u64)),
+            connection: Arc::new(connection),
+            flusher: None,
         }
     }
 
@@ -27,17 +131,55 @@ impl BatchedConnection {
         benchmark: Option<ID>,
         comment: Option<String>,
     ) -> Result<(), ConnectionError> {
-        self.connection.init(config, benchmark, comment)
+        // the connection is only shared with the flusher after its initialization
+        Arc::get_mut(&mut self.connection)
+            .ok_or(ConnectionError::AlreadyInitialized)?
+            .init(config, benchmark, comment)?;
+
+        if let (Some(timeout), None) = (self.timeout, &self.flusher) {
+            self.flusher = Some(Flusher::spawn(
+                timeout,
+                self.connection.clone(),
+                self.buffer.clone(),
+            ));
+        }
+
+        Ok(())
     }
 
     pub fn close(self) -> Result<(), ConnectionError> {
-        let buffer = Arc::try_unwrap(self.buffer).unwrap_or_log().into_inner();
+        if let Some(flusher) = self.flusher {
+            flusher.stop();
+        }
 
-        if !buffer.is_empty() {
-            self.connection.store_iter(buffer.into_iter())?;
+        let batch = Arc::try_unwrap(self.buffer).unwrap_or_log().into_inner();
+        let connection = Arc::try_unwrap(self.connection).unwrap_or_log();
+
+        if !batch.bundles.is_empty() {
+            connection.store_iter(batch.bundles.into_iter())?;
         }
 
-        self.connection.close()
+        connection.close()
+    }
+
+    pub fn completed_runs(&self) -> Result<CompletedRuns, ConnectionError> {
+        self.connection.completed_runs()
+    }
+
+    pub fn benchmark(&self) -> ID {
+        self.connection.benchmark()
+    }
+
+    pub fn reserve_run_id(&self) -> Result<ID, ConnectionError> {
+        self.connection.reserve_run_id()
+    }
+
+    pub fn mark_interrupted(&self) -> Result<(), ConnectionError> {
+        self.connection.mark_interrupted()
+    }
+
+    pub fn store_instance(&self, features: &InstanceFeatures) -> Result<(), ConnectionError> {
+        self.connection.store_instance(features)
     }
 
     pub fn store(
@@ -46,22 +188,31 @@ impl BatchedConnection {
         solver: CowStr,
         test_set: CowStr,
         target: &PathBuf,
+        instance: Option<CowStr>,
     ) -> Result<i32, ConnectionError> {
-        let mut buffer = self.buffer.lock_arc();
+        let id = self.connection.reserve_run_id()?;
+        let mut batch = self.buffer.lock_arc();
 
-        buffer.push(MetricsBundle {
+        batch.bundles.push(MetricsBundle {
             metrics,
             solver,
             test_set,
             target: target.clone(),
+            instance,
+            id: Some(id),
         });
 
-        if buffer.len() as u32 == self.size {
-            self.connection.store_iter(buffer.drain(0..))?;
-
-            buffer.clear();
+        // the run is buffered regardless, a failed flush is retried with the next one
+        if batch.bundles.len() as u32 >= self.size {
+            if let Err(e) = batch.flush(&self.connection) {
+                error!(
+                    error = ?e,
+                    "Failed to flush {} buffered runs, retrying with the next flush: {e}",
+                    batch.bundles.len()
+                );
+            }
         }
 
-        Ok(i32::MIN)
+        Ok(id)
     }
 }
@@ -0,0 +1,116 @@
+use super::{
+    batched::BatchedConnection, sqlite::SharedConnection, ConnectionAdapter, ConnectionError,
+    RunStatus, TestMetrics,
+};
+use crate::{
+    config::{BatchConfig, ConnectionConfig, SolverConfig},
+    test_util::TempPath,
+};
+use rusqlite::Connection;
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+const CONFIG: &str = "
+executor: !Local
+  pinned: false
+database: {}
+ingest:
+  yaml: !Null
+solvers:
+  minisat:
+    exec: /bin/true
+    ingest: yaml
+tests:
+  set:
+    timeout: 1000
+    collector: !Glob
+      glob: '*.cnf'
+      path: /tmp
+";
+
+fn batched(path: &Path, size: u32, timeout: Option<u32>) -> BatchedConnection {
+    let connection = SharedConnection::load(&ConnectionConfig::SQLite {
+        path: path.to_path_buf(),
+    })
+    .map(ConnectionAdapter::SQLite)
+    .unwrap();
+    let mut batched = BatchedConnection::load(&BatchConfig { size, timeout }, connection);
+
+    batched
+        .init(
+            &serde_yaml::from_str::<SolverConfig>(CONFIG).unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    batched
+}
+
+fn store(connection: &BatchedConnection, solver: &str) {
+    connection
+        .store(
+            TestMetrics::with_status(RunStatus::Timeout),
+            solver.into(),
+            "set".into(),
+            &PathBuf::from("a.cnf"),
+            None,
+        )
+        .unwrap();
+}
+
+fn stored_runs(path: &Path) -> i64 {
+    Connection::open(path)
+        .unwrap()
+        .query_row("select count(*) from runs", [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn flush_after_timeout() {
+    let path = TempPath::new("timeout.db");
+    let mut connection = batched(&path, 100, Some(50));
+
+    store(&connection, "minisat");
+    store(&connection, "minisat");
+
+    // the batch isn't full, the flusher stores it once the timeout elapsed
+    let start = Instant::now();
+    while stored_runs(&path) < 2 && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(stored_runs(&path), 2);
+
+    // the flusher keeps the connection, it can't be initialized again
+    assert!(matches!(
+        connection.init(&serde_yaml::from_str(CONFIG).unwrap(), None, None),
+        Err(ConnectionError::AlreadyInitialized)
+    ));
+
+    // the remaining runs are flushed when the flusher is stopped
+    store(&connection, "minisat");
+    connection.close().unwrap();
+    assert_eq!(stored_runs(&path), 3);
+}
+
+#[test]
+fn failed_flush_keeps_runs() {
+    let path = TempPath::new("failed.db");
+    let connection = batched(&path, 2, None);
+
+    // the full batch fails to flush due to a solver that isn't registered
+    store(&connection, "minisat");
+    store(&connection, "unknown");
+    assert_eq!(stored_runs(&path), 0);
+
+    // the runs are still buffered and fail again on close instead of being dropped
+    store(&connection, "minisat");
+    assert!(matches!(
+        connection.close(),
+        Err(ConnectionError::DanglingReference(_))
+    ));
+    assert_eq!(stored_runs(&path), 0);
+}
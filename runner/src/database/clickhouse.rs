@@ -1,2 +1,935 @@
-#[derive(Debug, Clone)]
-pub struct CHConnection {}
+use super::{
+    transfer::{BenchmarkRecord, MetricRecord, RunRecord, SolverRecord, TestSetRecord},
+    util::{metric_insert, metric_type, quoted, registered_id, IDMap, MetricTables},
+    CompletedRuns, ConnectionError, MetricSchema, MetricType, MetricValue, Metrics, MetricsBundle,
+    RunStatus, TestMetrics, ID,
+};
+use crate::{
+    collector::dimacs::InstanceFeatures,
+    config::{ConnectionConfig, SolverConfig},
+};
+use clickhouse::{Client, Row};
+use cowstr::CowStr;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    iter::Iterator,
+    mem,
+    path::PathBuf,
+    sync::atomic::{AtomicI32, Ordering},
+};
+use thiserror::Error;
+use tokio::runtime::{Builder, Runtime};
+use tracing::{debug, error, info, trace, warn};
+use tracing_unwrap::ResultExt;
+
+/// Connection to a ClickHouse server
+///
+/// The clickhouse client is async, all requests are executed on an owned tokio runtime to keep the
+/// interface identical to the other adapters.
+pub struct CHConnection {
+    client: Client,
+    runtime: Runtime,
+    solvers: IDMap,
+    test_sets: IDMap,
+    benchmark: ID,
+    // ClickHouse has no sequences, run IDs are handed out from the highest known ID instead
+    next_run: AtomicI32,
+    metric_tables: MetricTables,
+}
+
+#[derive(Error, Debug)]
+pub enum ClickHouseError {
+    #[error("ClickHouse Error")]
+    ClickHouse(#[from] clickhouse::error::Error),
+    #[error("Failed to build async runtime")]
+    Runtime(#[from] std::io::Error),
+}
+
+impl From<clickhouse::error::Error> for ConnectionError {
+    fn from(value: clickhouse::error::Error) -> Self {
+        ConnectionError::ClickHouse(ClickHouseError::ClickHouse(value))
+    }
+}
+
+#[derive(Row, Serialize, Deserialize, Debug)]
+pub(super) struct BenchmarkRow {
+    id: ID,
+    comment: String,
+}
+
+#[derive(Row, Serialize, Deserialize, Debug)]
+pub(super) struct InstanceRow {
+    hash: String,
+    variables: u32,
+    clauses: u64,
+    literals: u64,
+    header_variables: u32,
+    header_clauses: u64,
+    ratio: f64,
+    horn_fraction: f64,
+    binary_fraction: f64,
+    clause_lengths: String,
+    min_occurrences: u64,
+    max_occurrences: u64,
+    mean_occurrences: f64,
+    stddev_occurrences: f64,
+}
+
+impl Debug for CHConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CHConnection")
+            .field("solvers", &self.solvers)
+            .field("test_sets", &self.test_sets)
+            .field("benchmark", &self.benchmark)
+            .field("next_run", &self.next_run)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CHConnection {
+    pub fn load(config: &ConnectionConfig) -> Result<Self, ConnectionError> {
+        match config {
+            ConnectionConfig::ClickHouse {
+                server,
+                database,
+                user,
+                password,
+                connections,
+                lz4,
+                lz4hc,
+            } => {
+                let mut builder = Builder::new_multi_thread();
+                builder.enable_all();
+
+                if let Some(connections) = connections {
+                    builder.worker_threads((*connections).max(1) as usize);
+                }
+
+                let runtime = builder
+                    .build()
+                    .map_err(|error| ConnectionError::ClickHouse(error.into()))?;
+
+                let mut client = Client::default()
+                    .with_url(server.to_string())
+                    .with_database(database);
+
+                if let (Some(user), Some(password)) = (user, password) {
+                    client = client.with_user(user).with_password(password);
+                }
+
+                #[cfg(feature = "clickhouse-lz4")]
+                {
+                    client = match (lz4, lz4hc) {
+                        (_, Some(level)) => {
+                            client.with_compression(clickhouse::Compression::Lz4Hc(*level as i32))
+                        }
+                        (Some(true), None) => client.with_compression(clickhouse::Compression::Lz4),
+                        _ => client.with_compression(clickhouse::Compression::None),
+                    };
+                }
+
+                #[cfg(not(feature = "clickhouse-lz4"))]
+                let _ = (lz4, lz4hc);
+
+                Ok(Self::new(client, runtime))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// create a connection from an existing client, e.g., one that points to a mock server
+    pub fn new(client: Client, runtime: Runtime) -> Self {
+        Self {
+            client,
+            runtime,
+            solvers: IDMap::new(),
+            test_sets: IDMap::new(),
+            benchmark: ID::MIN,
+            next_run: AtomicI32::new(1),
+            metric_tables: MetricTables::default(),
+        }
+    }
+
+    pub fn init(
+        &mut self,
+        config: &SolverConfig,
+        benchmark: Option<ID>,
+        comment: Option<String>,
+    ) -> Result<(), ConnectionError> {
+        let client = self.client.clone();
+
+        self.apply_schema()?;
+
+        if let Some(benchmark_id) = benchmark {
+            self.benchmark = benchmark_id;
+
+            // a continued benchmark is only interrupted if it is interrupted again
+            self.set_interrupted(false)?;
+        } else {
+            self.benchmark = self.new_benchmark(comment)?;
+        }
+
+        // pre-register all solvers and test sets in database
+        for (name, solver) in config.solvers.iter() {
+            let results = self.runtime.block_on(
+                client
+                    .query("select ?fields from solvers where name = ?")
+                    .bind(name.as_str())
+                    .fetch_all::<SolverRecord>(),
+            )?;
+
+            let current_params = solver.get_params();
+            let current_exec = solver.exec.to_string_lossy();
+
+            match results.into_iter().find(|row| {
+                row.exec == current_exec
+                    && row.ingest == solver.ingest.as_str()
+                    && row.params == current_params
+            }) {
+                Some(row) => {
+                    info!(name = %name, id = %row.id, "Was able to reuse existing solver entry");
+                    self.solvers.insert(name.clone(), row.id);
+                }
+                None => {
+                    let id = self.next_id("solvers")?;
+                    let row = SolverRecord {
+                        id,
+                        name: name.to_string(),
+                        exec: current_exec.to_string(),
+                        params: current_params,
+                        ingest: solver.ingest.to_string(),
+                        assignment: solver.get_assignment(),
+                    };
+
+                    self.runtime.block_on(async {
+                        let mut insert = client.insert("solvers")?;
+                        insert.write(&row).await?;
+                        insert.end().await
+                    })?;
+
+                    info!(name = %name, id = %id, "Created solver entry");
+                    self.solvers.insert(name.clone(), id);
+                }
+            }
+        }
+
+        for (name, set) in config.tests.iter() {
+            let results = self.runtime.block_on(
+                client
+                    .query("select ?fields from test_sets where name = ?")
+                    .bind(name.as_str())
+                    .fetch_all::<TestSetRecord>(),
+            )?;
+
+            let current_params = set.get_params();
+
+            match results
+                .into_iter()
+                .find(|row| row.timeout == set.timeout && row.params == current_params)
+            {
+                Some(row) => {
+                    info!(name = %name, id = %row.id, "Was able to reuse existing test set entry");
+                    self.test_sets.insert(name.clone(), row.id);
+                }
+                None => {
+                    let id = self.next_id("test_sets")?;
+                    let row = TestSetRecord {
+                        id,
+                        timeout: set.timeout,
+                        name: name.to_string(),
+                        params: current_params,
+                    };
+
+                    self.runtime.block_on(async {
+                        let mut insert = client.insert("test_sets")?;
+                        insert.write(&row).await?;
+                        insert.end().await
+                    })?;
+
+                    info!(name = %name, id = %id, "Created set entry");
+                    self.test_sets.insert(name.clone(), id);
+                }
+            }
+        }
+
+        self.next_run = AtomicI32::new(self.next_id("runs")?);
+
+        self.metric_tables = MetricTables::new(config);
+        self.create_metric_tables()?;
+
+        Ok(())
+    }
+
+    /// create the metric tables of all ingestors and add newly declared metrics to existing ones
+    fn create_metric_tables(&self) -> Result<(), ConnectionError> {
+        for (table, schema) in self.metric_tables.iter() {
+            self.create_metric_table(&table, schema)?;
+        }
+
+        Ok(())
+    }
+
+    /// create the metric `table` unless it exists and add the missing columns of `schema`
+    pub fn create_metric_table(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+    ) -> Result<(), ConnectionError> {
+        self.runtime.block_on(
+            self.client
+                .query(&format!(
+                    "create table if not exists {} (run Int32) engine = MergeTree order by run",
+                    quoted(table)
+                ))
+                .execute(),
+        )?;
+
+        for (name, metric_type) in schema.iter() {
+            self.runtime.block_on(
+                self.client
+                    .query(&format!(
+                        "alter table {} add column if not exists {} {}",
+                        quoted(table),
+                        quoted(name),
+                        column_type(*metric_type)
+                    ))
+                    .execute(),
+            )?;
+        }
+
+        debug!(table = %table, "Created metric table {table}");
+
+        Ok(())
+    }
+
+    /// insert the declared metrics of a run into the table of its ingestor
+    async fn insert_metrics(
+        &self,
+        id: ID,
+        solver: &CowStr,
+        metrics: &Metrics,
+    ) -> Result<(), clickhouse::error::Error> {
+        if let Some((statement, values)) = self.metric_tables.insert(solver, metrics) {
+            values
+                .into_iter()
+                .fold(self.client.query(&statement).bind(id), |query, value| {
+                    query.bind(value)
+                })
+                .execute()
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn apply_schema(&self) -> Result<(), ConnectionError> {
+        let mut counter = 1;
+
+        for table in SQL_SCHEMA {
+            match self.runtime.block_on(self.client.query(table).execute()) {
+                Ok(()) => info!("Applied SQL schema ({counter}/{SQL_SCHEMA_NUMBER})"),
+                Err(error) => {
+                    error!(error = ?error, "Failed to apply SQL schema ({counter}/{SQL_SCHEMA_NUMBER}): {error}");
+                    trace!("schema: {table}");
+
+                    return Err(error.into());
+                }
+            }
+
+            counter += 1;
+        }
+
+        Ok(())
+    }
+
+    /// retrieve the next free ID of a table
+    /// NOTE: This is not safe against concurrent writers on other nodes
+    fn next_id(&self, table: &str) -> Result<ID, ConnectionError> {
+        let max = self.runtime.block_on(
+            self.client
+                .query(&format!("select max(id) from {table}"))
+                .fetch_one::<ID>(),
+        )?;
+
+        Ok(max + 1)
+    }
+
+    fn new_benchmark(&mut self, comment: Option<String>) -> Result<ID, ConnectionError> {
+        let id = self.next_id("benchmarks")?;
+        let row = BenchmarkRow {
+            id,
+            comment: comment.unwrap_or_default(),
+        };
+        let client = self.client.clone();
+
+        self.runtime.block_on(async {
+            let mut insert = client.insert("benchmarks")?;
+            insert.write(&row).await?;
+            insert.end().await
+        })?;
+
+        info!(id = id, "Created new benchmark");
+
+        Ok(id)
+    }
+
+    pub fn close(self) -> Result<(), ConnectionError> {
+        self.runtime.shutdown_background();
+
+        info!("Closed ClickHouse connection");
+
+        Ok(())
+    }
+
+    fn row(
+        &self,
+        metrics: TestMetrics,
+        solver: &CowStr,
+        test_set: &CowStr,
+        target: &PathBuf,
+        instance: Option<CowStr>,
+        id: Option<ID>,
+    ) -> Result<RunRecord, ConnectionError> {
+        Ok(RunRecord {
+            id: id.unwrap_or_else(|| self.next_run.fetch_add(1, Ordering::SeqCst)),
+            runtime: if metrics.runtime == 0 {
+                None
+            } else {
+                Some(metrics.runtime)
+            },
+            parse_time: metrics.parse_time,
+            satisfiable: metrics.satisfiable as i8,
+            memory_usage: metrics.memory_usage,
+            restarts: metrics.restarts,
+            conflicts: metrics.conflicts,
+            propagations: metrics.propagations,
+            conflict_literals: metrics.conflict_literals,
+            number_of_variables: metrics.number_of_variables,
+            number_of_clauses: metrics.number_of_clauses,
+            max_rss: metrics.resources.max_rss,
+            user_time: metrics.resources.user_time,
+            system_time: metrics.resources.system_time,
+            voluntary_context_switches: metrics.resources.voluntary_context_switches,
+            involuntary_context_switches: metrics.resources.involuntary_context_switches,
+            minor_page_faults: metrics.resources.minor_page_faults,
+            major_page_faults: metrics.resources.major_page_faults,
+            status: metrics.status.kind(),
+            status_code: metrics.status.code(),
+            verification: metrics.verification as i8,
+            target: target.to_string_lossy().to_string(),
+            instance: instance.map(|hash| hash.to_string()),
+            solver: registered_id(&self.solvers, "solvers", solver)?,
+            test: registered_id(&self.test_sets, "test_sets", test_set)?,
+            benchmark: self.benchmark,
+        })
+    }
+
+    pub fn store(
+        &self,
+        mut metrics: TestMetrics,
+        solver: CowStr,
+        test_set: CowStr,
+        target: &PathBuf,
+        instance: Option<CowStr>,
+    ) -> Result<ID, ConnectionError> {
+        debug!("Inserting {metrics:?}...");
+
+        let extra = mem::take(&mut metrics.extra);
+        let row = self.row(metrics, &solver, &test_set, target, instance, None)?;
+        let id = row.id;
+
+        self.runtime.block_on(async {
+            let mut insert = self.client.insert("runs")?;
+            insert.write(&row).await?;
+            insert.end().await?;
+
+            self.insert_metrics(id, &solver, &extra).await
+        })?;
+
+        Ok(id)
+    }
+
+    /// flag the current benchmark as interrupted, this is applied asynchronously as a mutation
+    pub fn set_interrupted(&self, interrupted: bool) -> Result<(), ConnectionError> {
+        self.runtime.block_on(
+            self.client
+                .query("alter table benchmarks update interrupted = ? where id = ?")
+                .bind(interrupted)
+                .bind(self.benchmark)
+                .execute(),
+        )?;
+
+        debug!(id = self.benchmark, "Set interrupted = {interrupted}");
+
+        Ok(())
+    }
+
+    pub fn benchmark(&self) -> ID {
+        self.benchmark
+    }
+
+    pub fn reserve_run_id(&self) -> Result<ID, ConnectionError> {
+        Ok(self.next_run.fetch_add(1, Ordering::SeqCst))
+    }
+
+    pub fn mark_interrupted(&self) -> Result<(), ConnectionError> {
+        self.set_interrupted(true)
+    }
+
+    pub fn completed_runs(&self) -> Result<CompletedRuns, ConnectionError> {
+        let rows = self.runtime.block_on(
+            self.client
+                .query(
+                    "select test, solver, ifNull(instance, target) as key, count() from runs
+                     where benchmark = ? and status not in (?, ?)
+                     group by test, solver, key",
+                )
+                .bind(self.benchmark)
+                .bind(RunStatus::SpawnError.kind())
+                .bind(RunStatus::IngestError.kind())
+                .fetch_all::<(ID, ID, String, u64)>(),
+        )?;
+
+        // only entries of the registered solvers and test sets are relevant
+        let name = |map: &IDMap, id: ID| {
+            map.iter()
+                .find(|(_, value)| **value == id)
+                .map(|(name, _)| name.clone())
+        };
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(test, solver, target, count)| {
+                Some((
+                    (
+                        name(&self.test_sets, test)?,
+                        name(&self.solvers, solver)?,
+                        target,
+                    ),
+                    count as usize,
+                ))
+            })
+            .collect())
+    }
+
+    /// store the features of an instance, duplicates are removed by the table engine
+    pub fn store_instance(&self, features: &InstanceFeatures) -> Result<(), ConnectionError> {
+        let row = InstanceRow {
+            hash: features.hash.clone(),
+            variables: features.variables,
+            clauses: features.clauses,
+            literals: features.literals,
+            header_variables: features.header_variables,
+            header_clauses: features.header_clauses,
+            ratio: features.ratio,
+            horn_fraction: features.horn_fraction,
+            binary_fraction: features.binary_fraction,
+            clause_lengths: serde_json::to_string(&features.clause_lengths).unwrap_or_log(),
+            min_occurrences: features.min_occurrences,
+            max_occurrences: features.max_occurrences,
+            mean_occurrences: features.mean_occurrences,
+            stddev_occurrences: features.stddev_occurrences,
+        };
+
+        self.runtime.block_on(async {
+            let mut insert = self.client.insert("instances")?;
+            insert.write(&row).await?;
+            insert.end().await
+        })?;
+
+        Ok(())
+    }
+
+    pub fn store_iter<I: Iterator<Item = MetricsBundle>>(
+        &self,
+        metrics: I,
+    ) -> Result<(), ConnectionError> {
+        // metrics are inserted once their runs exist
+        let mut extras = Vec::new();
+        // rows are built upfront, a run of an unknown solver or test set fails before the insert
+        let rows = metrics
+            .map(|mut bundle| {
+                let extra = mem::take(&mut bundle.metrics.extra);
+                let row = self.row(
+                    bundle.metrics,
+                    &bundle.solver,
+                    &bundle.test_set,
+                    &bundle.target,
+                    bundle.instance,
+                    bundle.id,
+                )?;
+
+                if !extra.is_empty() {
+                    extras.push((row.id, bundle.solver, extra));
+                }
+
+                Ok(row)
+            })
+            .collect::<Result<Vec<_>, ConnectionError>>()?;
+
+        let counter = self.runtime.block_on(async move {
+            let mut insert = self.client.insert("runs")?;
+            let mut counter = 0;
+
+            for row in rows.iter() {
+                counter += 1;
+                trace!(id = row.id, "Appending entry");
+
+                insert.write(row).await?;
+            }
+
+            insert.end().await?;
+
+            for (id, solver, extra) in extras {
+                self.insert_metrics(id, &solver, &extra).await?;
+            }
+
+            Ok::<usize, clickhouse::error::Error>(counter)
+        })?;
+
+        info!("Stored {counter} entries");
+
+        Ok(())
+    }
+
+    /// all rows of `table` for a transfer
+    fn records<T: Row + for<'a> Deserialize<'a>>(
+        &self,
+        table: &str,
+    ) -> Result<Vec<T>, ConnectionError> {
+        Ok(self.runtime.block_on(
+            self.client
+                .query(&format!("select ?fields from {table} order by id"))
+                .fetch_all::<T>(),
+        )?)
+    }
+
+    pub fn benchmarks(&self) -> Result<Vec<BenchmarkRecord>, ConnectionError> {
+        self.records("benchmarks")
+    }
+
+    pub fn solvers(&self) -> Result<Vec<SolverRecord>, ConnectionError> {
+        self.records("solvers")
+    }
+
+    pub fn test_sets(&self) -> Result<Vec<TestSetRecord>, ConnectionError> {
+        self.records("test_sets")
+    }
+
+    pub fn instances(&self) -> Result<Vec<InstanceFeatures>, ConnectionError> {
+        let rows = self.runtime.block_on(
+            self.client
+                .query("select ?fields from instances final")
+                .fetch_all::<InstanceRow>(),
+        )?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(InstanceFeatures {
+                    clause_lengths: serde_json::from_str(&row.clause_lengths)?,
+                    hash: row.hash,
+                    variables: row.variables,
+                    clauses: row.clauses,
+                    literals: row.literals,
+                    header_variables: row.header_variables,
+                    header_clauses: row.header_clauses,
+                    ratio: row.ratio,
+                    horn_fraction: row.horn_fraction,
+                    binary_fraction: row.binary_fraction,
+                    min_occurrences: row.min_occurrences,
+                    max_occurrences: row.max_occurrences,
+                    mean_occurrences: row.mean_occurrences,
+                    stddev_occurrences: row.stddev_occurrences,
+                })
+            })
+            .collect()
+    }
+
+    /// up to `limit` runs with an ID larger than `after` in the order of their IDs
+    pub fn runs(&self, after: ID, limit: usize) -> Result<Vec<RunRecord>, ConnectionError> {
+        Ok(self.runtime.block_on(
+            self.client
+                .query("select ?fields from runs where id > ? order by id limit ?")
+                .bind(after)
+                .bind(limit as u64)
+                .fetch_all::<RunRecord>(),
+        )?)
+    }
+
+    pub fn max_run_id(&self) -> Result<ID, ConnectionError> {
+        Ok(self.next_id("runs")? - 1)
+    }
+
+    /// insert `rows` into `table` with a single insert
+    fn insert_records<T: Row + Serialize>(
+        &self,
+        table: &str,
+        rows: &[T],
+    ) -> Result<(), ConnectionError> {
+        self.runtime.block_on(async {
+            let mut insert = self.client.insert(table)?;
+
+            for row in rows {
+                insert.write(row).await?;
+            }
+
+            insert.end().await
+        })?;
+
+        Ok(())
+    }
+
+    pub fn insert_benchmark(&self, benchmark: &BenchmarkRecord) -> Result<(), ConnectionError> {
+        self.insert_records("benchmarks", std::slice::from_ref(benchmark))
+    }
+
+    pub fn insert_solver(&self, solver: &SolverRecord) -> Result<(), ConnectionError> {
+        self.insert_records("solvers", std::slice::from_ref(solver))
+    }
+
+    pub fn insert_test_set(&self, test_set: &TestSetRecord) -> Result<(), ConnectionError> {
+        self.insert_records("test_sets", std::slice::from_ref(test_set))
+    }
+
+    /// insert runs with their IDs, a single insert is applied atomically by ClickHouse
+    pub fn insert_runs(&self, runs: &[RunRecord]) -> Result<(), ConnectionError> {
+        self.insert_records("runs", runs)
+    }
+
+    /// metric tables of all ingestors with their columns
+    pub fn metric_tables(&self) -> Result<BTreeMap<String, MetricSchema>, ConnectionError> {
+        let columns = self.runtime.block_on(
+            self.client
+                .query(
+                    "select table, name, type from system.columns
+                     where database = currentDatabase() and endsWith(table, '_metrics')
+                        and name != 'run'
+                     order by table, position",
+                )
+                .fetch_all::<(String, String, String)>(),
+        )?;
+        let mut schemas = BTreeMap::<String, MetricSchema>::new();
+
+        for (table, name, declared) in columns {
+            let schema = schemas.entry(table.clone()).or_default();
+
+            match metric_type(&declared, column_type) {
+                Some(metric_type) => {
+                    schema.insert(name.into(), metric_type);
+                }
+                None => warn!(
+                    table = %table,
+                    "Ignoring column {name} of {table} with the unsupported type {declared}"
+                ),
+            }
+        }
+
+        Ok(schemas)
+    }
+
+    /// Up to `limit` rows of the metric `table` with a run ID larger than `after`
+    ///
+    /// The columns are read one by one as their types are only known at runtime.
+    pub fn metric_rows(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+        after: ID,
+        limit: usize,
+    ) -> Result<Vec<MetricRecord>, ConnectionError> {
+        let runs = self.runtime.block_on(
+            self.client
+                .query(&format!(
+                    "select run from {} where run > ? order by run limit ?",
+                    quoted(table)
+                ))
+                .bind(after)
+                .bind(limit as u64)
+                .fetch_all::<ID>(),
+        )?;
+
+        let (Some(first), Some(last)) = (runs.first(), runs.last()) else {
+            return Ok(Vec::new());
+        };
+        let mut rows = runs
+            .iter()
+            .map(|run| (*run, vec![None; schema.len()]))
+            .collect::<BTreeMap<_, _>>();
+
+        for (index, (name, metric_type)) in schema.iter().enumerate() {
+            let query = self
+                .client
+                .query(&format!(
+                    "select run, {} from {} where run between ? and ? order by run",
+                    quoted(name),
+                    quoted(table)
+                ))
+                .bind(first)
+                .bind(last);
+            let values = match metric_type {
+                MetricType::Int => self
+                    .runtime
+                    .block_on(query.fetch_all::<(ID, Option<i64>)>())?
+                    .into_iter()
+                    .map(|(run, value)| (run, value.map(MetricValue::Int)))
+                    .collect_vec(),
+                MetricType::Float => self
+                    .runtime
+                    .block_on(query.fetch_all::<(ID, Option<f64>)>())?
+                    .into_iter()
+                    .map(|(run, value)| (run, value.map(MetricValue::Float)))
+                    .collect_vec(),
+                MetricType::String => self
+                    .runtime
+                    .block_on(query.fetch_all::<(ID, Option<String>)>())?
+                    .into_iter()
+                    .map(|(run, value)| (run, value.map(MetricValue::String)))
+                    .collect_vec(),
+            };
+
+            for (run, value) in values {
+                if let Some(row) = rows.get_mut(&run) {
+                    row[index] = value;
+                }
+            }
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|(run, values)| MetricRecord { run, values })
+            .collect())
+    }
+
+    /// largest run ID of the metric `table`, 0 if it is empty
+    pub fn max_metric_run(&self, table: &str) -> Result<ID, ConnectionError> {
+        Ok(self.runtime.block_on(
+            self.client
+                .query(&format!("select max(run) from {}", quoted(table)))
+                .fetch_one::<ID>(),
+        )?)
+    }
+
+    /// insert rows of the metric `table` in the order of their runs, each row is inserted on its own
+    pub fn insert_metric_rows(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+        rows: &[MetricRecord],
+    ) -> Result<(), ConnectionError> {
+        for row in rows {
+            // missing metrics are left to the column default
+            let (columns, values): (Vec<_>, Vec<_>) = schema
+                .keys()
+                .zip(row.values.iter())
+                .filter_map(|(name, value)| Some((name, value.as_ref()?)))
+                .unzip();
+
+            self.runtime.block_on(
+                values
+                    .into_iter()
+                    .fold(
+                        self.client
+                            .query(&metric_insert(table, columns.into_iter()))
+                            .bind(row.run),
+                        |query, value| query.bind(value),
+                    )
+                    .execute(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// column type of a metric declared by an ingestor
+fn column_type(metric_type: MetricType) -> &'static str {
+    match metric_type {
+        MetricType::Int => "Nullable(Int64)",
+        MetricType::Float => "Nullable(Float64)",
+        MetricType::String => "Nullable(String)",
+    }
+}
+
+// ref: https://clickhouse.com/docs/en/sql-reference/data-types
+//      https://clickhouse.com/docs/en/engines/table-engines/mergetree-family/mergetree
+pub const SQL_SCHEMA: [&str; 7] = [
+    "create table if not exists benchmarks (
+    id Int32,
+    comment String,
+    interrupted Bool default false
+) engine = MergeTree order by id",
+    // benchmarks tables of older runners lack the interrupted flag
+    "alter table benchmarks add column if not exists interrupted Bool default false",
+    "create table if not exists test_sets (
+    id Int32,
+    timeout UInt32,
+    name String,
+    params String
+) engine = MergeTree order by id",
+    "create table if not exists solvers (
+    id Int32,
+    name String,
+    exec String,
+    params String,
+    ingest String,
+    assignment Nullable(String)
+) engine = MergeTree order by id",
+    // solvers tables of older runners lack the assignment of swept variants
+    "alter table solvers add column if not exists assignment Nullable(String)",
+    "create table if not exists runs (
+    id Int32,
+
+    runtime Nullable(UInt64),
+    parse_time UInt64,
+    satisfiable Int8,
+    memory_usage UInt32,
+    restarts UInt32,
+    conflicts UInt32,
+    propagations UInt32,
+
+    conflict_literals UInt32,
+    number_of_variables UInt32,
+    number_of_clauses UInt32,
+
+    max_rss UInt64,
+    user_time UInt64,
+    system_time UInt64,
+    voluntary_context_switches UInt64,
+    involuntary_context_switches UInt64,
+    minor_page_faults UInt64,
+    major_page_faults UInt64,
+    status Int8,
+    status_code Nullable(Int32),
+    verification Int8,
+
+    target String,
+    instance Nullable(String),
+
+    solver Int32,
+    test Int32,
+    benchmark Int32
+) engine = MergeTree order by (benchmark, id)",
+    "create table if not exists instances (
+    hash String,
+
+    variables UInt32,
+    clauses UInt64,
+    literals UInt64,
+    header_variables UInt32,
+    header_clauses UInt64,
+
+    ratio Float64,
+    horn_fraction Float64,
+    binary_fraction Float64,
+    clause_lengths String,
+
+    min_occurrences UInt64,
+    max_occurrences UInt64,
+    mean_occurrences Float64,
+    stddev_occurrences Float64
+) engine = ReplacingMergeTree order by hash",
+];
+pub const SQL_SCHEMA_NUMBER: usize = SQL_SCHEMA.len();
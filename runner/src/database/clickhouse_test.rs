@@ -0,0 +1,133 @@
+use super::{
+    clickhouse::{BenchmarkRow, CHConnection, SQL_SCHEMA_NUMBER},
+    transfer::{RunRecord, SolverRecord, TestSetRecord},
+    MetricsBundle, Satisfiability, TestMetrics, ID,
+};
+use crate::config::SolverConfig;
+use clickhouse::{
+    test::{handlers, Mock},
+    Client,
+};
+use tokio::runtime::Runtime;
+
+const CONFIG: &str = "
+executor: !Local
+  pinned: false
+database: {}
+ingest:
+  yaml: !Null
+solvers:
+  minisat:
+    exec: /bin/true
+    ingest: yaml
+tests:
+  set:
+    timeout: 1000
+    collector: !Glob
+      glob: '*.cnf'
+      path: /tmp
+";
+
+fn mocked() -> (Mock, CHConnection) {
+    let runtime = Runtime::new().unwrap();
+    // the mock server is spawned on the runtime of the connection
+    let mock = {
+        let _guard = runtime.enter();
+        Mock::new()
+    };
+    let client = Client::default().with_url(mock.url());
+
+    (mock, CHConnection::new(client, runtime))
+}
+
+#[test]
+pub fn init_registers_entries() {
+    let (mock, mut connection) = mocked();
+    let config: SolverConfig = serde_yaml::from_str(CONFIG).unwrap();
+
+    for _ in 0..SQL_SCHEMA_NUMBER {
+        mock.add(handlers::record_ddl());
+    }
+    mock.add(handlers::provide(vec![6 as ID]));
+    let benchmarks = mock.add(handlers::record::<BenchmarkRow>());
+    mock.add(handlers::provide(Vec::<SolverRecord>::new()));
+    mock.add(handlers::provide(vec![0 as ID]));
+    let solvers = mock.add(handlers::record::<SolverRecord>());
+    mock.add(handlers::provide(Vec::<TestSetRecord>::new()));
+    mock.add(handlers::provide(vec![2 as ID]));
+    let test_sets = mock.add(handlers::record::<TestSetRecord>());
+    mock.add(handlers::provide(vec![0 as ID]));
+
+    connection
+        .init(&config, None, Some("comment".to_owned()))
+        .unwrap();
+
+    let runtime = Runtime::new().unwrap();
+    let benchmarks: Vec<BenchmarkRow> = runtime.block_on(benchmarks.collect());
+    let solvers: Vec<SolverRecord> = runtime.block_on(solvers.collect());
+    let test_sets: Vec<TestSetRecord> = runtime.block_on(test_sets.collect());
+
+    assert_eq!(benchmarks.len(), 1);
+    assert_eq!(benchmarks[0].id, 7);
+    assert_eq!(benchmarks[0].comment, "comment");
+    assert_eq!(solvers.len(), 1);
+    assert_eq!(solvers[0].id, 1);
+    assert_eq!(solvers[0].name, "minisat");
+    assert_eq!(test_sets.len(), 1);
+    assert_eq!(test_sets[0].id, 3);
+    assert_eq!(test_sets[0].timeout, 1000);
+}
+
+#[test]
+pub fn store_iter_single_insert() {
+    let (mock, mut connection) = mocked();
+    let config: SolverConfig = serde_yaml::from_str(CONFIG).unwrap();
+
+    for _ in 0..SQL_SCHEMA_NUMBER {
+        mock.add(handlers::record_ddl());
+    }
+    // continued benchmarks are no longer interrupted
+    mock.add(handlers::record_ddl());
+    mock.add(handlers::provide(vec![SolverRecord {
+        id: 4,
+        name: "minisat".to_owned(),
+        exec: "/bin/true".to_owned(),
+        params: "".to_owned(),
+        ingest: "yaml".to_owned(),
+        assignment: None,
+    }]));
+    mock.add(handlers::provide(vec![TestSetRecord {
+        id: 5,
+        timeout: 1000,
+        name: "set".to_owned(),
+        params: "".to_owned(),
+    }]));
+    mock.add(handlers::provide(vec![41 as ID]));
+
+    connection.init(&config, Some(3), None).unwrap();
+
+    let runs = mock.add(handlers::record::<RunRecord>());
+    let mut metrics = TestMetrics::failed();
+    metrics.satisfiable = Satisfiability::Satisfiable;
+
+    connection
+        .store_iter(["a.cnf", "b.cnf"].into_iter().map(|target| MetricsBundle {
+            metrics: metrics.clone(),
+            solver: "minisat".into(),
+            test_set: "set".into(),
+            target: target.into(),
+            instance: None,
+            id: None,
+        }))
+        .unwrap();
+
+    let runs: Vec<RunRecord> = Runtime::new().unwrap().block_on(runs.collect());
+
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs.iter().map(|run| run.id).collect::<Vec<_>>(), [42, 43]);
+    assert!(runs
+        .iter()
+        .all(|run| run.solver == 4 && run.test == 5 && run.benchmark == 3));
+    assert_eq!(runs[0].satisfiable, 1);
+    assert_eq!(runs[0].runtime, None);
+}
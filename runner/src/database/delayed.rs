@@ -1,14 +1,23 @@
-use super::{ConnectionAdapter, ConnectionError, MetricsBundle, TestMetrics, ID};
-use crate::config::SolverConfig;
+use super::{
+    spool::{self, Spool, SpoolRecord},
+    CompletedRuns, ConnectionAdapter, ConnectionError, MetricsBundle, TestMetrics, ID,
+};
+use crate::{collector::dimacs::InstanceFeatures, config::SolverConfig};
 use cowstr::CowStr;
-use parking_lot::FairMutex;
-use std::{path::PathBuf, sync::Arc};
-use tracing_unwrap::ResultExt;
+use std::{fs::remove_file, path::PathBuf};
+use tracing::{error, info};
+use tracing_unwrap::OptionExt;
 
+/// Connection that stores all runs at once when it is closed
+///
+/// Runs are written to an on-disk spool instead of being kept in memory, the spool is replayed on
+/// `close` and can be recovered with `satan-runner recover` if the runner crashed.
 #[derive(Debug)]
 pub struct DelayedConnection {
     adapter: Box<ConnectionAdapter>,
-    buffer: Arc<FairMutex<Vec<MetricsBundle>>>,
+    spool_path: PathBuf,
+    // created on `init` once the benchmark is known
+    spool: Option<Spool>,
 }
 
 impl DelayedConnection {
@@ -18,40 +27,84 @@ impl DelayedConnection {
         benchmark: Option<ID>,
         comment: Option<String>,
     ) -> Result<(), ConnectionError> {
-        self.adapter.init(config, benchmark, comment)
+        self.adapter.init(config, benchmark, comment)?;
+        self.spool = Some(Spool::create(&self.spool_path, self.adapter.benchmark())?);
+
+        info!(path = ?self.spool_path, "Spooling runs to {}", self.spool_path.to_string_lossy());
+
+        Ok(())
     }
 
-    pub fn load(connection: ConnectionAdapter) -> Self {
+    pub fn load(connection: ConnectionAdapter, spool_path: PathBuf) -> Self {
         Self {
-            buffer: Arc::new(FairMutex::new(Vec::new())),
             adapter: Box::new(connection),
+            spool_path,
+            spool: None,
         }
     }
 
     pub fn close(self) -> Result<(), ConnectionError> {
-        let buffer = Arc::try_unwrap(self.buffer).unwrap_or_log().into_inner();
+        if let Some(spool) = self.spool {
+            let (_, runs) = spool::read(spool.path())?;
+
+            if let Err(e) = self.adapter.store_iter(runs) {
+                error!(
+                    error = ?e,
+                    "Failed to store spooled runs, recover them with `satan-runner recover {}`",
+                    spool.path().to_string_lossy()
+                );
+
+                return Err(e);
+            }
 
-        if !buffer.is_empty() {
-            self.adapter.store_iter(buffer.into_iter())?;
+            remove_file(spool.path())?;
         }
 
         self.adapter.close()
     }
 
+    pub fn benchmark(&self) -> ID {
+        self.adapter.benchmark()
+    }
+
+    pub fn reserve_run_id(&self) -> Result<ID, ConnectionError> {
+        self.adapter.reserve_run_id()
+    }
+
+    pub fn completed_runs(&self) -> Result<CompletedRuns, ConnectionError> {
+        self.adapter.completed_runs()
+    }
+
+    pub fn mark_interrupted(&self) -> Result<(), ConnectionError> {
+        self.adapter.mark_interrupted()
+    }
+
+    pub fn store_instance(&self, features: &InstanceFeatures) -> Result<(), ConnectionError> {
+        self.adapter.store_instance(features)
+    }
+
     pub fn store(
         &self,
         metrics: TestMetrics,
         solver: CowStr,
         test_set: CowStr,
         target: &PathBuf,
+        instance: Option<CowStr>,
     ) -> Result<i32, ConnectionError> {
-        self.buffer.lock_arc().push(MetricsBundle {
-            metrics,
-            solver,
-            test_set,
-            target: target.to_path_buf(),
-        });
-
-        Ok(i32::MIN)
+        let id = self.adapter.reserve_run_id()?;
+
+        self.spool
+            .as_ref()
+            .expect_or_log("Delayed connection wasn't initialized")
+            .append(&SpoolRecord::Run(MetricsBundle {
+                metrics,
+                solver,
+                test_set,
+                target: target.to_path_buf(),
+                instance,
+                id: Some(id),
+            }))?;
+
+        Ok(id)
     }
 }
@@ -1,14 +1,33 @@
-use super::{util::IDMap, MetricsBundle, TestMetrics, ID};
+use super::{
+    export::{export_query, export_row, metrics_json, ExportFilter, ExportRow, EXPORT_COLUMNS},
+    transfer::{
+        record_queries, run_record, BenchmarkRecord, MetricRecord, RunRecord, SolverRecord,
+        TestSetRecord, RUN_COLUMNS, RUN_SELECT,
+    },
+    util::{
+        metric_insert, metric_type, quoted, registered_id, IDMap, MetricTables, Migration,
+        SchemaVersion,
+    },
+    CompletedRuns, ConflictRow, MetricSchema, MetricType, MetricValue, Metrics, MetricsBundle,
+    RunStatus, TestMetrics, ID,
+};
 use crate::{
+    collector::dimacs::InstanceFeatures,
     config::{ConnectionConfig, SolverConfig},
     database::ConnectionError,
 };
 use cowstr::CowStr;
-use duckdb::params;
+use duckdb::{params, params_from_iter, types::Value};
 use parking_lot::{lock_api::ArcMutexGuard, FairMutex, RawFairMutex};
-use std::{fmt::Debug, iter::Iterator, path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    iter::{self, Iterator},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use thiserror::Error;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 use tracing_unwrap::ResultExt;
 
 #[derive(Debug)]
@@ -21,6 +40,7 @@ pub struct InnerConnection {
     solvers: IDMap,
     test_sets: IDMap,
     benchmark: i32,
+    metric_tables: MetricTables,
 }
 
 #[derive(Error, Debug)]
@@ -44,7 +64,7 @@ impl SharedConnection {
         self.0.lock_arc()
     }
 
-    fn lock(&self) -> ArcMutexGuard<RawFairMutex, InnerConnection> {
+    pub(super) fn lock(&self) -> ArcMutexGuard<RawFairMutex, InnerConnection> {
         self.0.lock_arc()
     }
 
@@ -65,14 +85,60 @@ impl SharedConnection {
         Ok(Self::new(InnerConnection::load(config)?))
     }
 
+    pub fn migrate(&mut self) -> Result<(SchemaVersion, SchemaVersion), ConnectionError> {
+        self.lock_mut().migrate()
+    }
+
+    pub fn check_schema(&self) -> Result<(), ConnectionError> {
+        self.lock().check_schema()
+    }
+
     pub fn store(
         &self,
         metrics: TestMetrics,
         solver: CowStr,
         test_set: CowStr,
         target: &PathBuf,
+        instance: Option<CowStr>,
     ) -> Result<i32, ConnectionError> {
-        self.lock().store(metrics, solver, test_set, target)
+        self.lock()
+            .store(metrics, solver, test_set, target, instance)
+    }
+
+    pub fn store_instance(&self, features: &InstanceFeatures) -> Result<(), ConnectionError> {
+        self.lock().store_instance(features)
+    }
+
+    pub fn completed_runs(&self) -> Result<CompletedRuns, ConnectionError> {
+        self.lock().completed_runs()
+    }
+
+    pub fn export_each<E: From<ConnectionError>>(
+        &self,
+        filter: &ExportFilter,
+        f: impl FnMut(ExportRow) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.lock().export_each(filter, f)
+    }
+
+    pub fn store_conflicts(
+        &self,
+        benchmark: ID,
+        conflicts: &[ConflictRow],
+    ) -> Result<(), ConnectionError> {
+        self.lock().store_conflicts(benchmark, conflicts)
+    }
+
+    pub fn mark_interrupted(&self) -> Result<(), ConnectionError> {
+        self.lock().set_interrupted(true)
+    }
+
+    pub fn benchmark(&self) -> ID {
+        self.lock().benchmark
+    }
+
+    pub fn reserve_run_id(&self) -> Result<ID, ConnectionError> {
+        self.lock().reserve_run_id()
     }
 
     pub fn store_iter<'a, I: Iterator<Item = MetricsBundle>>(
@@ -100,25 +166,14 @@ impl InnerConnection {
         benchmark: Option<ID>,
         comment: Option<String>,
     ) -> Result<(), ConnectionError> {
-        let mut counter = 1;
-
-        for table in SQL_SCHEMA {
-            match self.connection.execute(table, []) {
-                Ok(_) => info!("Applied SQL schema ({counter}/{SQL_SCHEMA_NUMBER})"),
-                Err(e) => {
-                    error!("Failed to apply SQL schema ({counter}/{SQL_SCHEMA_NUMBER}): {e}",);
-                    trace!("schema: {table}");
-
-                    return Err(ConnectionError::DuckDB(DuckDBError::DuckDB(e)));
-                }
-            };
-
-            counter += 1;
-        }
+        self.apply_schema()?;
 
         if let Some(benchmark_id) = benchmark {
             self.benchmark = benchmark_id;
 
+            // a continued benchmark is only interrupted if it is interrupted again
+            self.set_interrupted(false)?;
+
             // TODO: Check if the comment should be updated
         } else {
             self.benchmark = self.new_benchmark(comment)?;
@@ -161,12 +216,13 @@ impl InnerConnection {
                 let tx = self.connection.transaction()?;
 
                 let id =tx.query_row(
-                "insert into solvers values (nextval('seq_solver_id'), ?, ?, ?, ?) returning id",
+                "insert into solvers (name, exec, params, ingest, assignment) values (?, ?, ?, ?, ?) returning id",
                 params![
                     name.as_str(),
                     solver.exec.to_string_lossy(),
                     solver.params.join(" "),
-                    solver.ingest.as_str()
+                    solver.ingest.as_str(),
+                    solver.get_assignment()
                 ],
                 |row| row.get(0),
             )?;
@@ -226,14 +282,177 @@ impl InnerConnection {
             }
         }
 
+        self.metric_tables = MetricTables::new(config);
+        self.create_metric_tables()?;
+
+        Ok(())
+    }
+
+    /// create the metric tables of all ingestors and add newly declared metrics to existing ones
+    fn create_metric_tables(&self) -> Result<(), ConnectionError> {
+        for (table, schema) in self.metric_tables.iter() {
+            self.create_metric_table(&table, schema)?;
+        }
+
+        Ok(())
+    }
+
+    /// create the metric `table` unless it exists and add the missing columns of `schema`
+    pub fn create_metric_table(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+    ) -> Result<(), ConnectionError> {
+        self.connection.execute(
+            &format!(
+                "create table if not exists {} (run integer primary key references runs (id))",
+                quoted(table)
+            ),
+            [],
+        )?;
+
+        let columns = table_columns(&self.connection, table)?;
+
+        for (name, metric_type) in schema.iter() {
+            if !columns.iter().any(|column| column == name.as_str()) {
+                self.connection.execute(
+                    &format!(
+                        "alter table {} add column {} {}",
+                        quoted(table),
+                        quoted(name),
+                        column_type(*metric_type)
+                    ),
+                    [],
+                )?;
+
+                info!(table = %table, "Added metric {name} to {table}");
+            }
+        }
+
         Ok(())
     }
 
+    fn apply_schema(&mut self) -> Result<(), ConnectionError> {
+        let (from, to) = self.migrate()?;
+
+        if from != to {
+            info!("Upgraded database schema from version {from} to {to}");
+        }
+
+        Ok(())
+    }
+
+    /// schema version of the database without upgrading it, `None` for new databases
+    pub fn schema_version(&self) -> Result<Option<SchemaVersion>, ConnectionError> {
+        let version = if has_table(&self.connection, "schema_version")? {
+            self.connection
+                .query_row("select max(version) from schema_version", [], |row| {
+                    row.get(0)
+                })?
+        } else {
+            None
+        };
+
+        Ok(match version {
+            // databases created before versioning
+            None if has_table(&self.connection, "runs")? => Some(1),
+            version => version,
+        })
+    }
+
+    /// fail unless the schema is up to date, for commands that must not upgrade it implicitly
+    pub fn check_schema(&self) -> Result<(), ConnectionError> {
+        match self.schema_version()?.unwrap_or(0) {
+            version if version > SCHEMA_VERSION => Err(ConnectionError::UnsupportedSchema(version)),
+            version if version < SCHEMA_VERSION => Err(ConnectionError::OutdatedSchema(version)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create the schema of a new database or upgrade an existing one with `MIGRATIONS`
+    ///
+    /// Returns the schema versions before and after, new databases start at version 0. Databases
+    /// of a newer version are rejected.
+    pub fn migrate(&mut self) -> Result<(SchemaVersion, SchemaVersion), ConnectionError> {
+        self.connection.execute(SCHEMA_VERSION_TABLE, [])?;
+
+        let version = match self.schema_version()? {
+            Some(version) if version > SCHEMA_VERSION => {
+                error!("Database schema version {version} is newer than the supported version {SCHEMA_VERSION}, please update the runner");
+
+                return Err(ConnectionError::UnsupportedSchema(version));
+            }
+            Some(version) => version,
+            None => {
+                let tx = self.connection.transaction()?;
+                let mut counter = 1;
+
+                for table in SQL_SCHEMA {
+                    match tx.execute(table, []) {
+                        Ok(_) => info!("Applied SQL schema ({counter}/{SQL_SCHEMA_NUMBER})"),
+                        Err(e) => {
+                            error!(
+                                "Failed to apply SQL schema ({counter}/{SQL_SCHEMA_NUMBER}): {e}",
+                            );
+                            trace!("schema: {table}");
+
+                            return Err(ConnectionError::DuckDB(DuckDBError::DuckDB(e)));
+                        }
+                    };
+
+                    counter += 1;
+                }
+
+                tx.execute(
+                    "insert into schema_version (version) values (?)",
+                    params![SCHEMA_VERSION],
+                )?;
+                tx.commit()?;
+
+                return Ok((0, SCHEMA_VERSION));
+            }
+        };
+
+        for (steps, target) in MIGRATIONS.iter().zip(2..).skip(version as usize - 1) {
+            let tx = self.connection.transaction()?;
+
+            for step in steps.iter() {
+                match step {
+                    Migration::Execute(statement) => {
+                        tx.execute(statement, [])?;
+                    }
+                    Migration::AddColumn {
+                        table,
+                        column,
+                        definition,
+                    } => {
+                        if !table_columns(&tx, table)?.iter().any(|name| name == column) {
+                            tx.execute(
+                                &format!("alter table {table} add column {column} {definition}"),
+                                [],
+                            )?;
+                        }
+                    }
+                }
+            }
+
+            tx.execute(
+                "insert into schema_version (version) values (?)",
+                params![target],
+            )?;
+            tx.commit()?;
+
+            info!("Migrated database schema to version {target}");
+        }
+
+        Ok((version, SCHEMA_VERSION))
+    }
+
     fn new_benchmark(&mut self, comment: Option<String>) -> Result<i32, ConnectionError> {
         let tx = self.connection.transaction()?;
 
         let id = tx.query_row(
-            "insert into benchmarks values (nextval('seq_benchmarks'), ?) returning id",
+            "insert into benchmarks (id, comment) values (nextval('seq_benchmarks'), ?) returning id",
             params![comment.unwrap_or("".to_owned())],
             |row| row.get(0),
         )?;
@@ -274,37 +493,52 @@ impl InnerConnection {
                     solvers: IDMap::new(),
                     test_sets: IDMap::new(),
                     benchmark: ID::MIN,
+                    metric_tables: MetricTables::default(),
                 })
             }
             _ => unreachable!(),
         }
     }
 
-    pub fn store(
+    /// insert a single run with `connection`, which may also be a transaction
+    fn insert_run(
         &self,
-        metrics: TestMetrics,
-        solver: CowStr,
-        test_set: CowStr,
-        target: &PathBuf,
-    ) -> Result<i32, ConnectionError> {
-        debug!("Inserting {metrics:?}...");
+        connection: &duckdb::Connection,
+        metrics: &TestMetrics,
+        solver: &CowStr,
+        test_set: &CowStr,
+        target: &Path,
+        instance: Option<&CowStr>,
+        id: Option<ID>,
+    ) -> Result<ID, ConnectionError> {
+        let id = match id {
+            Some(id) => id,
+            None => self.reserve_run_id()?,
+        };
 
-        let result = self
-            .connection
+        let id: ID = connection
             .prepare_cached(
-                "insert into runs values
-                (nextval('seq_run_id'), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "insert into runs
+                (id, runtime, parse_time, satisfiable, memory_usage, restarts, conflicts,
+                 propagations, conflict_literals, number_of_variables,
+                 number_of_clauses, max_rss, user_time, system_time,
+                 voluntary_context_switches, involuntary_context_switches,
+                 minor_page_faults, major_page_faults, status, status_code, verification,
+                 target, instance, solver, test, benchmark)
+                values
+                (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 returning id",
             )?
             .query_row(
                 params![
+                    id,
                     if metrics.runtime == 0 {
                         None
                     } else {
                         Some(metrics.runtime)
                     },
                     metrics.parse_time,
-                    metrics.satisfiable as i8,
+                    metrics.satisfiable.clone() as i8,
                     metrics.memory_usage,
                     metrics.restarts,
                     metrics.conflicts,
@@ -312,68 +546,475 @@ impl InnerConnection {
                     metrics.conflict_literals,
                     metrics.number_of_variables,
                     metrics.number_of_clauses,
+                    metrics.resources.max_rss,
+                    metrics.resources.user_time,
+                    metrics.resources.system_time,
+                    metrics.resources.voluntary_context_switches,
+                    metrics.resources.involuntary_context_switches,
+                    metrics.resources.minor_page_faults,
+                    metrics.resources.major_page_faults,
+                    metrics.status.kind(),
+                    metrics.status.code(),
+                    metrics.verification as i8,
                     target.to_string_lossy().as_ref(),
-                    self.solvers.get(&solver).unwrap(),
-                    self.test_sets.get(&test_set).unwrap(),
+                    instance.map(|hash| hash.as_str()),
+                    registered_id(&self.solvers, "solvers", solver)?,
+                    registered_id(&self.test_sets, "test_sets", test_set)?,
                     self.benchmark
                 ],
                 |row| row.get(0),
-            )
-            .map_err(|err| ConnectionError::DuckDB(DuckDBError::DuckDB(err)));
+            )?;
+
+        if let Some((statement, values)) = self.metric_tables.insert(solver, &metrics.extra) {
+            connection
+                .prepare_cached(&statement)?
+                .execute(params_from_iter(
+                    iter::once(Value::from(id)).chain(values.into_iter().map(metric_value)),
+                ))?;
+        }
 
-        match result {
-            Ok(result) => Ok(result),
-            Err(err) => Err(err),
+        Ok(id)
+    }
+
+    pub fn store(
+        &self,
+        metrics: TestMetrics,
+        solver: CowStr,
+        test_set: CowStr,
+        target: &PathBuf,
+        instance: Option<CowStr>,
+    ) -> Result<i32, ConnectionError> {
+        debug!("Inserting {metrics:?}...");
+
+        self.insert_run(
+            &self.connection,
+            &metrics,
+            &solver,
+            &test_set,
+            target,
+            instance.as_ref(),
+            None,
+        )
+    }
+
+    /// reserve the next ID of the run sequence
+    pub fn reserve_run_id(&self) -> Result<ID, ConnectionError> {
+        Ok(self
+            .connection
+            .prepare_cached("select nextval('seq_run_id')")?
+            .query_row([], |row| row.get(0))?)
+    }
+
+    /// flag the current benchmark as interrupted, i.e., not all of its runs were executed
+    pub fn set_interrupted(&self, interrupted: bool) -> Result<(), ConnectionError> {
+        self.connection
+            .prepare_cached("update benchmarks set interrupted = ? where id = ?")?
+            .execute(params![interrupted, self.benchmark])?;
+
+        debug!(id = self.benchmark, "Set interrupted = {interrupted}");
+
+        Ok(())
+    }
+
+    /// pass the runs matching `filter` with the attributes of their benchmark, solver and test
+    /// set to `f` one by one, ordered by their IDs
+    pub fn export_each<E: From<ConnectionError>>(
+        &self,
+        filter: &ExportFilter,
+        mut f: impl FnMut(ExportRow) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let tables = self.metric_tables()?;
+        let metrics = tables
+            .values()
+            .flat_map(|schema| schema.keys())
+            .collect::<Vec<_>>();
+        let mut parameters = vec![Value::from(filter.benchmark)];
+        parameters.extend(
+            filter
+                .solvers
+                .iter()
+                .chain(filter.test_sets.iter())
+                .map(|name| Value::from(name.clone())),
+        );
+
+        let read = |row: &duckdb::Row| -> Result<ExportRow, ConnectionError> {
+            let mut values = Metrics::new();
+
+            // only the table of the ingestor of the solver contains the run
+            for (index, name) in metrics.iter().enumerate() {
+                if let Some(value) = metric_from_value(row.get(EXPORT_COLUMNS + index)?) {
+                    values.insert((*name).clone(), value);
+                }
+            }
+
+            let mut export = export_row!(row);
+            export.metrics = metrics_json(&values)?;
+
+            Ok(export)
+        };
+
+        let mut statement = self
+            .connection
+            .prepare(&export_query(filter, &tables))
+            .map_err(ConnectionError::from)?;
+        let mut rows = statement
+            .query(params_from_iter(parameters.iter()))
+            .map_err(ConnectionError::from)?;
+
+        while let Some(row) = rows.next().map_err(ConnectionError::from)? {
+            f(read(row)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// replace the answer conflicts recorded for `benchmark`, checks may be repeated
+    pub fn store_conflicts(
+        &self,
+        benchmark: ID,
+        conflicts: &[ConflictRow],
+    ) -> Result<(), ConnectionError> {
+        let tx = self.connection.unchecked_transaction()?;
+
+        tx.execute(
+            "delete from answer_conflicts where benchmark = ?",
+            params![benchmark],
+        )?;
+
+        {
+            let mut statement = tx.prepare_cached(
+                "insert into answer_conflicts (run, benchmark, instance, satisfiable)
+                 values (?, ?, ?, ?)",
+            )?;
+
+            for conflict in conflicts {
+                statement.execute(params![
+                    conflict.run,
+                    benchmark,
+                    conflict.instance,
+                    conflict.satisfiable
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        debug!(
+            benchmark = benchmark,
+            "Recorded {} runs with conflicting answers",
+            conflicts.len()
+        );
+
+        Ok(())
+    }
+
+    pub fn completed_runs(&self) -> Result<CompletedRuns, ConnectionError> {
+        // only entries of the registered solvers and test sets are relevant
+        let solvers = self
+            .solvers
+            .iter()
+            .map(|(name, id)| (*id, name.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let test_sets = self
+            .test_sets
+            .iter()
+            .map(|(name, id)| (*id, name.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut completed = CompletedRuns::new();
+        let mut statement = self.connection.prepare(
+            "select test, solver, coalesce(instance, target), count(*) from runs
+             where benchmark = ? and status not in (?, ?)
+             group by test, solver, coalesce(instance, target)",
+        )?;
+        let mut rows = statement.query(params![
+            self.benchmark,
+            RunStatus::SpawnError.kind(),
+            RunStatus::IngestError.kind()
+        ])?;
+
+        while let Some(row) = rows.next()? {
+            let (test, solver): (ID, ID) = (row.get(0)?, row.get(1)?);
+
+            if let (Some(test), Some(solver)) = (test_sets.get(&test), solvers.get(&solver)) {
+                let count: i64 = row.get(3)?;
+
+                completed.insert((test.clone(), solver.clone(), row.get(2)?), count as usize);
+            }
         }
+
+        Ok(completed)
+    }
+
+    /// store the features of an instance, instances with a known hash are ignored
+    pub fn store_instance(&self, features: &InstanceFeatures) -> Result<(), ConnectionError> {
+        self.connection
+            .prepare_cached(
+                "insert or ignore into instances
+                (hash, variables, clauses, literals, header_variables, header_clauses, ratio,
+                 horn_fraction, binary_fraction, clause_lengths, min_occurrences,
+                 max_occurrences, mean_occurrences, stddev_occurrences)
+                values (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                features.hash,
+                features.variables,
+                features.clauses,
+                features.literals,
+                features.header_variables,
+                features.header_clauses,
+                features.ratio,
+                features.horn_fraction,
+                features.binary_fraction,
+                serde_json::to_string(&features.clause_lengths).unwrap_or_log(),
+                features.min_occurrences,
+                features.max_occurrences,
+                features.mean_occurrences,
+                features.stddev_occurrences
+            ])?;
+
+        Ok(())
     }
 
     pub fn store_iter<'a, I: Iterator<Item = MetricsBundle>>(
-        &mut self,
+        &self,
         mut metrics: I,
     ) -> Result<(), ConnectionError> {
-        let tx = self.connection.transaction()?;
-        let mut appender = tx.appender("runs")?;
+        // the appender can't be used with sequences, a prepared statement in a single
+        // transaction is used instead
+        let tx = self.connection.unchecked_transaction()?;
         let mut counter = 0;
 
         metrics.try_for_each(|bundle| -> Result<(), ConnectionError> {
             counter += 1;
-            appender
-                .append_row(params![
-                    "nextval('seq_run_id')",
-                    bundle.metrics.runtime,
-                    bundle.metrics.parse_time,
-                    bundle.metrics.satisfiable.clone() as i8,
-                    bundle.metrics.memory_usage,
-                    bundle.metrics.restarts,
-                    bundle.metrics.conflicts,
-                    bundle.metrics.propagations,
-                    bundle.metrics.number_of_variables,
-                    bundle.metrics.number_of_clauses,
-                    bundle.target.to_string_lossy().as_ref(),
-                    self.solvers.get(&bundle.solver).unwrap(),
-                    self.test_sets.get(&bundle.test_set).unwrap(),
-                    self.benchmark
-                ])
-                .map_err(ConnectionError::from)
+            let id = self.insert_run(
+                &tx,
+                &bundle.metrics,
+                &bundle.solver,
+                &bundle.test_set,
+                &bundle.target,
+                bundle.instance.as_ref(),
+                bundle.id,
+            )?;
+
+            trace!(id = id, "Inserted entry");
+
+            Ok(())
         })?;
 
-        drop(appender);
         tx.commit()?;
 
         info!("Stored {counter} entries");
 
         Ok(())
     }
+
+    record_queries!();
+
+    /// advance `sequence` past `id`, rows inserted with an explicit ID don't use the sequence
+    fn advance_sequence(&self, sequence: &str, id: ID) -> Result<(), ConnectionError> {
+        let next: ID =
+            self.connection
+                .query_row(&format!("select nextval('{sequence}')"), [], |row| {
+                    row.get(0)
+                })?;
+
+        if next < id {
+            self.connection.query_row(
+                &format!("select max(nextval('{sequence}')) from range(?)"),
+                params![(id - next) as i64],
+                |row| row.get::<_, Option<i64>>(0),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// column type of a metric declared by an ingestor
+fn column_type(metric_type: MetricType) -> &'static str {
+    match metric_type {
+        MetricType::Int => "bigint",
+        MetricType::Float => "double",
+        MetricType::String => "varchar",
+    }
+}
+
+fn metric_value(value: &MetricValue) -> Value {
+    match value {
+        MetricValue::Int(value) => Value::BigInt(*value),
+        MetricValue::Float(value) => Value::Double(*value),
+        MetricValue::String(value) => Value::Text(value.clone()),
+    }
 }
 
-// TODO: Document below, maybe add some kind of migration utility
+/// value of a metric column, `None` if the run didn't report the metric
+fn metric_from_value(value: Value) -> Option<MetricValue> {
+    match value {
+        Value::BigInt(value) => Some(MetricValue::Int(value)),
+        Value::Int(value) => Some(MetricValue::Int(value.into())),
+        Value::Double(value) => Some(MetricValue::Float(value)),
+        Value::Float(value) => Some(MetricValue::Float(value.into())),
+        Value::Text(value) => Some(MetricValue::String(value)),
+        _ => None,
+    }
+}
+
+/// names of all tables
+const TABLES: &str = "select table_name from information_schema.tables";
+
+/// whether `table` exists in the database
+fn has_table(connection: &duckdb::Connection, table: &str) -> Result<bool, ConnectionError> {
+    Ok(connection
+        .prepare(TABLES)?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == table))
+}
+
+/// list the columns of `table` in the order of their definition
+fn table_columns(
+    connection: &duckdb::Connection,
+    table: &str,
+) -> Result<Vec<String>, ConnectionError> {
+    connection
+        .prepare(&format!("select name from pragma_table_info('{table}')"))?
+        .query_map([], |row| row.get(0))?
+        .try_fold(Vec::new(), |mut init, result| {
+            init.push(result?);
+
+            Ok(init)
+        })
+}
+
+const INSTANCES_SCHEMA: &str = "create table if not exists instances (
+    hash varchar primary key,
+
+    variables uinteger not null,
+    clauses ubigint not null,
+    literals ubigint not null,
+    header_variables uinteger not null,
+    header_clauses ubigint not null,
+
+    ratio double not null,
+    horn_fraction double not null,
+    binary_fraction double not null,
+    clause_lengths varchar not null,
+
+    min_occurrences ubigint not null,
+    max_occurrences ubigint not null,
+    mean_occurrences double not null,
+    stddev_occurrences double not null
+);";
+
+const CONFLICTS_SCHEMA: &str = "create table if not exists answer_conflicts (
+    run integer primary key references runs (id),
+    benchmark integer not null references benchmarks (id),
+    instance varchar not null,
+    satisfiable tinyint not null
+);";
+
+const SCHEMA_VERSION_TABLE: &str = "create table if not exists schema_version (
+    version integer primary key,
+    applied_at timestamp default current_timestamp
+);";
+
+// Steps from each schema version to the next, starting with version 1, i.e., `MIGRATIONS[0]`
+// upgrades a database from version 1 to version 2. New migrations have to be mirrored in
+// `SQL_SCHEMA`.
+// NOTE: DuckDB doesn't support constraints on added columns, migrated columns stay nullable
+pub const MIGRATIONS: [&[Migration]; 6] = [
+    // resource usage and run status
+    &[
+        Migration::AddColumn {
+            table: "runs",
+            column: "max_rss",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "user_time",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "system_time",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "voluntary_context_switches",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "involuntary_context_switches",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "minor_page_faults",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "major_page_faults",
+            definition: "ubigint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "status",
+            definition: "tinyint default 0",
+        },
+        Migration::AddColumn {
+            table: "runs",
+            column: "status_code",
+            definition: "integer",
+        },
+    ],
+    // instance features
+    &[
+        Migration::Execute(INSTANCES_SCHEMA),
+        Migration::AddColumn {
+            table: "runs",
+            column: "instance",
+            definition: "varchar",
+        },
+    ],
+    // answer verification
+    &[Migration::AddColumn {
+        table: "runs",
+        column: "verification",
+        definition: "tinyint default 0",
+    }],
+    // interrupted benchmarks
+    &[Migration::AddColumn {
+        table: "benchmarks",
+        column: "interrupted",
+        definition: "boolean default false",
+    }],
+    // answer conflicts
+    &[Migration::Execute(CONFLICTS_SCHEMA)],
+    // parameter sweeps
+    &[Migration::AddColumn {
+        table: "solvers",
+        column: "assignment",
+        definition: "varchar",
+    }],
+];
+
+/// schema version of `SQL_SCHEMA`, i.e., after applying all migrations
+pub const SCHEMA_VERSION: SchemaVersion = MIGRATIONS.len() as SchemaVersion + 1;
+
+// Schema of new databases, existing databases are upgraded with `MIGRATIONS`
 // ref: https://duckdb.org/docs/sql/statements/create_table.html
 //      https://duckdb.org/docs/sql/data_types/overview
-pub const SQL_SCHEMA: [&str; 8] = [
+pub const SQL_SCHEMA: [&str; 10] = [
     "create sequence if not exists seq_benchmarks start 1 no cycle;",
     "create table if not exists benchmarks (
     id integer primary key default(nextval('seq_benchmarks')),
-    comment varchar
+    comment varchar,
+    interrupted boolean not null default false
 );",
     "create sequence if not exists seq_testset start 1 no cycle;",
     "create table if not exists test_sets (
@@ -388,8 +1029,10 @@ pub const SQL_SCHEMA: [&str; 8] = [
     name varchar not null,
     exec varchar not null,
     params varchar not null,
-    ingest varchar not null
+    ingest varchar not null,
+    assignment varchar
 );",
+    INSTANCES_SCHEMA,
     "create sequence if not exists seq_run_id start 1 no cycle;",
     "create table if not exists runs (
     id integer primary key default(nextval('seq_run_id')),
@@ -405,11 +1048,25 @@ pub const SQL_SCHEMA: [&str; 8] = [
 	conflict_literals uinteger not null,
 	number_of_variables uinteger not null,
     number_of_clauses uinteger not null,
+
+    max_rss ubigint not null default 0,
+    user_time ubigint not null default 0,
+    system_time ubigint not null default 0,
+    voluntary_context_switches ubigint not null default 0,
+    involuntary_context_switches ubigint not null default 0,
+    minor_page_faults ubigint not null default 0,
+    major_page_faults ubigint not null default 0,
+    status tinyint not null default 0,
+    status_code integer,
+    verification tinyint not null default 0,
+
     target string not null,
+    instance varchar references instances (hash),
 
     solver integer not null references solvers (id),
     test integer not null references test_sets (id),
     benchmark integer not null references benchmarks (id)
 );",
+    CONFLICTS_SCHEMA,
 ];
 pub const SQL_SCHEMA_NUMBER: usize = SQL_SCHEMA.len();
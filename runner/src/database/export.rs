@@ -0,0 +1,332 @@
+use super::{util::quoted, ConnectionError, MetricSchema, Metrics, ID};
+use itertools::Itertools;
+use parquet::{
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    record::RecordWriter,
+};
+use parquet_derive::ParquetRecordWriter;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Failed to write export")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to write CSV")]
+    Csv(#[from] csv::Error),
+    #[error("Failed to write JSON")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to write Parquet")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("Failed to collect runs")]
+    Connection(#[from] ConnectionError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Runs that should be exported, empty solver and test set lists include all of them
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    pub benchmark: ID,
+    pub solvers: Vec<String>,
+    pub test_sets: Vec<String>,
+}
+
+/// A run with the attributes of its benchmark, solver and test set
+///
+/// Unsigned columns are exported as signed integers as not all formats support them.
+#[derive(Debug, Clone, Default, Serialize, ParquetRecordWriter)]
+pub struct ExportRow {
+    pub run: i32,
+    pub benchmark: i32,
+    pub comment: String,
+    pub interrupted: bool,
+    pub solver: String,
+    pub exec: String,
+    pub solver_params: String,
+    pub ingest: String,
+    pub test_set: String,
+    pub timeout: i64,
+    pub test_set_params: String,
+    pub target: String,
+    pub instance: Option<String>,
+    pub runtime: Option<i64>,
+    pub parse_time: i64,
+    pub satisfiable: i32,
+    pub memory_usage: i64,
+    pub restarts: i64,
+    pub conflicts: i64,
+    pub propagations: i64,
+    pub conflict_literals: i64,
+    pub number_of_variables: i64,
+    pub number_of_clauses: i64,
+    pub max_rss: i64,
+    pub user_time: i64,
+    pub system_time: i64,
+    pub voluntary_context_switches: i64,
+    pub involuntary_context_switches: i64,
+    pub minor_page_faults: i64,
+    pub major_page_faults: i64,
+    pub status: i32,
+    pub status_code: Option<i32>,
+    pub verification: i32,
+    // parameter values of a swept solver variant as JSON object
+    pub solver_assignment: Option<String>,
+    // solver specific metrics declared by the ingestor as JSON object
+    pub metrics: Option<String>,
+}
+
+#[cfg(test)]
+impl ExportRow {
+    /// satisfiable run of `solver` on `target` with a timeout of 100ms, all counters are zero
+    pub fn fixture(solver: &str, target: &str) -> Self {
+        Self {
+            benchmark: 1,
+            solver: solver.to_owned(),
+            exec: format!("/usr/bin/{solver}"),
+            ingest: "null".to_owned(),
+            test_set: "sat".to_owned(),
+            timeout: 100,
+            target: target.to_owned(),
+            runtime: Some(10),
+            satisfiable: 1,
+            ..Self::default()
+        }
+    }
+}
+
+/// number of columns read by `export_row`, the columns of the metric tables follow
+pub const EXPORT_COLUMNS: usize = 34;
+
+/// Build an `ExportRow` from a row of `export_query`
+///
+/// SQLite and DuckDB rows share the same accessors, but not a common trait.
+macro_rules! export_row {
+    ($row:expr) => {
+        $crate::database::export::ExportRow {
+            run: $row.get(0)?,
+            benchmark: $row.get(1)?,
+            comment: $row.get(2)?,
+            interrupted: $row.get(3)?,
+            solver: $row.get(4)?,
+            exec: $row.get(5)?,
+            solver_params: $row.get(6)?,
+            ingest: $row.get(7)?,
+            test_set: $row.get(8)?,
+            timeout: $row.get(9)?,
+            test_set_params: $row.get(10)?,
+            target: $row.get(11)?,
+            instance: $row.get(12)?,
+            runtime: $row.get(13)?,
+            parse_time: $row.get(14)?,
+            satisfiable: $row.get(15)?,
+            memory_usage: $row.get(16)?,
+            restarts: $row.get(17)?,
+            conflicts: $row.get(18)?,
+            propagations: $row.get(19)?,
+            conflict_literals: $row.get(20)?,
+            number_of_variables: $row.get(21)?,
+            number_of_clauses: $row.get(22)?,
+            max_rss: $row.get(23)?,
+            user_time: $row.get(24)?,
+            system_time: $row.get(25)?,
+            voluntary_context_switches: $row.get(26)?,
+            involuntary_context_switches: $row.get(27)?,
+            minor_page_faults: $row.get(28)?,
+            major_page_faults: $row.get(29)?,
+            status: $row.get(30)?,
+            status_code: $row.get(31)?,
+            verification: $row.get(32)?,
+            solver_assignment: $row.get(33)?,
+            metrics: None,
+        }
+    };
+}
+pub(crate) use export_row;
+
+/// Query for all runs matching `filter` in the column order of `export_row`
+///
+/// The benchmark is bound to the first parameter, followed by the solver and test set names.
+/// Columns added by migrations are nullable in DuckDB and thus replaced by their defaults. The
+/// columns of all metric `tables` follow in the order of their schemas.
+pub fn export_query(filter: &ExportFilter, tables: &BTreeMap<String, MetricSchema>) -> String {
+    let metric_columns = tables
+        .values()
+        .enumerate()
+        .flat_map(|(index, schema)| {
+            schema
+                .keys()
+                .map(move |column| format!(", metrics_{index}.{}", quoted(column)))
+        })
+        .join("");
+    let metric_joins = tables
+        .keys()
+        .enumerate()
+        .map(|(index, table)| {
+            format!(
+                " left join {} as metrics_{index} on metrics_{index}.run = runs.id",
+                quoted(table)
+            )
+        })
+        .join("");
+
+    let mut query = "select
+        runs.id, benchmarks.id, coalesce(benchmarks.comment, ''),
+        coalesce(benchmarks.interrupted, false),
+        solvers.name, solvers.exec, solvers.params, solvers.ingest,
+        test_sets.name, cast(test_sets.timeout as bigint), test_sets.params,
+        runs.target, runs.instance,
+        cast(runs.runtime as bigint), cast(runs.parse_time as bigint),
+        cast(runs.satisfiable as integer), cast(runs.memory_usage as bigint),
+        cast(runs.restarts as bigint), cast(runs.conflicts as bigint),
+        cast(runs.propagations as bigint), cast(runs.conflict_literals as bigint),
+        cast(runs.number_of_variables as bigint), cast(runs.number_of_clauses as bigint),
+        cast(coalesce(runs.max_rss, 0) as bigint), cast(coalesce(runs.user_time, 0) as bigint),
+        cast(coalesce(runs.system_time, 0) as bigint),
+        cast(coalesce(runs.voluntary_context_switches, 0) as bigint),
+        cast(coalesce(runs.involuntary_context_switches, 0) as bigint),
+        cast(coalesce(runs.minor_page_faults, 0) as bigint),
+        cast(coalesce(runs.major_page_faults, 0) as bigint),
+        cast(coalesce(runs.status, 0) as integer), runs.status_code,
+        cast(coalesce(runs.verification, 0) as integer), solvers.assignment"
+        .to_owned();
+
+    query += &format!(
+        "{metric_columns}
+    from runs
+        join benchmarks on benchmarks.id = runs.benchmark
+        join solvers on solvers.id = runs.solver
+        join test_sets on test_sets.id = runs.test{metric_joins}
+    where runs.benchmark = ?"
+    );
+
+    if !filter.solvers.is_empty() {
+        query += &format!(
+            " and solvers.name in ({})",
+            filter.solvers.iter().map(|_| "?").join(", ")
+        );
+    }
+
+    if !filter.test_sets.is_empty() {
+        query += &format!(
+            " and test_sets.name in ({})",
+            filter.test_sets.iter().map(|_| "?").join(", ")
+        );
+    }
+
+    query + " order by runs.id"
+}
+
+/// metrics of a run as JSON object, `None` if it didn't report any
+pub fn metrics_json(metrics: &Metrics) -> Result<Option<String>, serde_json::Error> {
+    if metrics.is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::to_string(metrics).map(Some)
+}
+
+/// number of rows per Parquet row group, rows are buffered until a group is full
+const ROW_GROUP_SIZE: usize = 65536;
+
+/// Writer of exported rows in any `ExportFormat`, the rows are written as they arrive
+pub enum ExportWriter {
+    Csv(csv::Writer<File>),
+    Jsonl(BufWriter<File>),
+    Parquet(SerializedFileWriter<File>, Vec<ExportRow>),
+}
+
+impl ExportWriter {
+    /// create the export file at `path`
+    pub fn create(format: ExportFormat, path: &Path) -> Result<Self, ExportError> {
+        let file = File::create(path)?;
+
+        Ok(match format {
+            ExportFormat::Csv => Self::Csv(csv::Writer::from_writer(file)),
+            ExportFormat::Jsonl => Self::Jsonl(BufWriter::new(file)),
+            ExportFormat::Parquet => {
+                // the derived schema only depends on the type of the rows
+                let schema = (&[] as &[ExportRow]).schema()?;
+                let writer = SerializedFileWriter::new(
+                    file,
+                    schema,
+                    Arc::new(WriterProperties::builder().build()),
+                )?;
+
+                Self::Parquet(writer, Vec::with_capacity(ROW_GROUP_SIZE))
+            }
+        })
+    }
+
+    pub fn write(&mut self, row: ExportRow) -> Result<(), ExportError> {
+        match self {
+            Self::Csv(writer) => writer.serialize(row)?,
+            Self::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &row)?;
+                writer.write_all(b"\n")?;
+            }
+            Self::Parquet(writer, rows) => {
+                rows.push(row);
+
+                if rows.len() >= ROW_GROUP_SIZE {
+                    write_row_group(writer, rows)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// write all buffered rows and close the file
+    pub fn finish(self) -> Result<(), ExportError> {
+        match self {
+            Self::Csv(mut writer) => writer.flush()?,
+            Self::Jsonl(mut writer) => writer.flush()?,
+            Self::Parquet(mut writer, mut rows) => {
+                if !rows.is_empty() {
+                    write_row_group(&mut writer, &mut rows)?;
+                }
+
+                writer.close()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// write `rows` as a row group and clear them
+fn write_row_group(
+    writer: &mut SerializedFileWriter<File>,
+    rows: &mut Vec<ExportRow>,
+) -> Result<(), ExportError> {
+    let mut row_group = writer.next_row_group()?;
+
+    rows.as_slice().write_to_row_group(&mut row_group)?;
+    row_group.close()?;
+    rows.clear();
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use super::export::{ExportFormat, ExportRow, ExportWriter};
+use crate::test_util::TempPath;
+use std::{
+    fs::{read, read_to_string},
+    path::Path,
+};
+
+fn row(run: i32, instance: Option<&str>) -> ExportRow {
+    ExportRow {
+        run,
+        comment: "nightly, with \"quotes\"".to_owned(),
+        solver_params: "-q".to_owned(),
+        ingest: "cadical".to_owned(),
+        timeout: 5000,
+        instance: instance.map(str::to_owned),
+        runtime: Some(1200),
+        parse_time: 3,
+        restarts: 2,
+        conflicts: 10,
+        propagations: 100,
+        conflict_literals: 30,
+        number_of_variables: 4,
+        number_of_clauses: 4,
+        max_rss: 2048,
+        user_time: 1100,
+        system_time: 100,
+        voluntary_context_switches: 1,
+        minor_page_faults: 12,
+        verification: 1,
+        ..ExportRow::fixture("cadical", &format!("{run}.cnf"))
+    }
+}
+
+fn write(rows: &[ExportRow], format: ExportFormat, path: &Path) {
+    let mut writer = ExportWriter::create(format, path).unwrap();
+
+    for row in rows {
+        writer.write(row.clone()).unwrap();
+    }
+
+    writer.finish().unwrap();
+}
+
+fn export_path(format: ExportFormat) -> TempPath {
+    TempPath::new(&format!("export.{}", format.extension()))
+}
+
+#[test]
+fn export_formats() {
+    let rows = vec![row(1, Some("abc")), row(2, None)];
+
+    let path = export_path(ExportFormat::Csv);
+    write(&rows, ExportFormat::Csv, &path);
+    let csv = read_to_string(&path).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("run,benchmark,comment,interrupted,solver,"));
+    assert!(lines[1].contains("\"nightly, with \"\"quotes\"\"\""));
+
+    let path = export_path(ExportFormat::Jsonl);
+    write(&rows, ExportFormat::Jsonl, &path);
+    let runs = read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["instance"], "abc");
+    assert!(runs[1]["instance"].is_null());
+
+    let path = export_path(ExportFormat::Parquet);
+    write(&rows, ExportFormat::Parquet, &path);
+    let parquet = read(&path).unwrap();
+
+    assert!(parquet.starts_with(b"PAR1") && parquet.ends_with(b"PAR1"));
+}
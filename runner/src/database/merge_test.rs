@@ -0,0 +1,175 @@
+use super::{export::ExportFilter, ConnectionAdapter, MetricValue, RunStatus, TestMetrics};
+use crate::{
+    config::{ConnectionConfig, DatabaseConfig, SolverConfig},
+    test_util::TempPath,
+};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+fn config(solvers: &[&str]) -> SolverConfig {
+    let solvers = solvers
+        .iter()
+        .map(|solver| format!("  {solver}:\n    exec: /bin/true\n    ingest: yaml\n"))
+        .collect::<String>();
+
+    serde_yaml::from_str(&format!(
+        "
+executor: !Local
+  pinned: false
+database: {{}}
+ingest:
+  yaml: !Exec
+    executable: /bin/true
+    timeout: 1000
+    metrics:
+      order: Int
+solvers:
+{solvers}tests:
+  set:
+    timeout: 1000
+    collector: !Glob
+      glob: '*.cnf'
+      path: /tmp
+"
+    ))
+    .unwrap()
+}
+
+fn connection(path: &Path) -> ConnectionAdapter {
+    ConnectionAdapter::load_connection(&DatabaseConfig {
+        delayed: false,
+        spool: None,
+        batched: None,
+        connection: ConnectionConfig::SQLite {
+            path: path.to_path_buf(),
+        },
+    })
+    .unwrap()
+}
+
+/// create a database with a run and metric of every solver
+fn database(name: &str, solvers: &[&str]) -> TempPath {
+    let path = TempPath::new(&format!("{name}.db"));
+    let mut connection = connection(&path);
+
+    connection.init(&config(solvers), None, None).unwrap();
+    for (index, solver) in solvers.iter().enumerate() {
+        // the metric name is a keyword and has to be quoted
+        let mut metrics = TestMetrics::with_status(RunStatus::Timeout);
+        metrics
+            .extra
+            .insert("order".into(), MetricValue::Int(index as i64));
+
+        connection
+            .store(
+                metrics,
+                (*solver).into(),
+                "set".into(),
+                &PathBuf::from(format!("{name}.cnf")),
+                None,
+            )
+            .unwrap();
+    }
+    connection.close().unwrap();
+
+    path
+}
+
+fn query(path: &Path, query: &str) -> Vec<(String, String)> {
+    Connection::open(path)
+        .unwrap()
+        .prepare(query)
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn merge_overlapping_solvers() {
+    let first = database("first", &["minisat", "kissat"]);
+    let second = database("second", &["minisat", "cadical"]);
+    let target = TempPath::new("target.db");
+    let mut connection = connection(&target);
+
+    let report = connection
+        .merge(&ConnectionConfig::SQLite {
+            path: first.to_path_buf(),
+        })
+        .unwrap();
+    assert_eq!(
+        (report.benchmarks, report.solvers_created, report.runs),
+        (1, 2, 2)
+    );
+
+    // the identical solver and test set of the second database are reused
+    let report = connection
+        .merge(&ConnectionConfig::SQLite {
+            path: second.to_path_buf(),
+        })
+        .unwrap();
+    assert_eq!(
+        (
+            report.benchmarks,
+            report.solvers_created,
+            report.solvers_reused
+        ),
+        (1, 1, 1)
+    );
+    assert_eq!((report.test_sets_created, report.test_sets_reused), (0, 1));
+    assert_eq!((report.runs, report.metrics), (2, 2));
+
+    // the metrics of the remapped runs are exported with them
+    let rows = connection
+        .export(&ExportFilter {
+            benchmark: 2,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        rows.iter()
+            .map(|row| (row.solver.as_str(), row.metrics.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("minisat", Some(r#"{"order":0}"#)),
+            ("cadical", Some(r#"{"order":1}"#))
+        ]
+    );
+    connection.close().unwrap();
+
+    // runs keep referencing their solvers, the IDs of the second database are remapped
+    assert_eq!(
+        query(
+            &target,
+            "select solvers.name, runs.target from runs
+             join solvers on solvers.id = runs.solver order by runs.id"
+        ),
+        vec![
+            ("minisat".to_owned(), "first.cnf".to_owned()),
+            ("kissat".to_owned(), "first.cnf".to_owned()),
+            ("minisat".to_owned(), "second.cnf".to_owned()),
+            ("cadical".to_owned(), "second.cnf".to_owned()),
+        ]
+    );
+    assert_eq!(
+        query(
+            &target,
+            "select cast(count(*) as text), cast(count(distinct benchmark) as text) from runs"
+        ),
+        vec![("4".to_owned(), "2".to_owned())]
+    );
+    assert_eq!(
+        query(
+            &target,
+            "select runs.target, cast(yaml_metrics.\"order\" as text) from yaml_metrics
+             join runs on runs.id = yaml_metrics.run order by runs.id"
+        ),
+        vec![
+            ("first.cnf".to_owned(), "0".to_owned()),
+            ("first.cnf".to_owned(), "1".to_owned()),
+            ("second.cnf".to_owned(), "0".to_owned()),
+            ("second.cnf".to_owned(), "1".to_owned()),
+        ]
+    );
+}
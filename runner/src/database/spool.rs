@@ -0,0 +1,107 @@
+use super::{ConnectionError, MetricsBundle, ID};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+use tracing::{debug, warn};
+
+/// A single line of a spool
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SpoolRecord {
+    // always the first record, runs are stored for this benchmark
+    Benchmark(ID),
+    Run(MetricsBundle),
+}
+
+/// Append-only JSON lines file that receives every run before it is buffered
+///
+/// Every record is appended and synced to the disk before the run is buffered, the spool thus
+/// survives crashes of the runner and the node with at most the last record being incomplete.
+#[derive(Debug)]
+pub struct Spool {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Spool {
+    /// create a new spool for `benchmark`, existing spools are never overwritten as they may
+    /// contain runs that weren't recovered yet
+    pub fn create(path: &Path, benchmark: ID) -> Result<Self, ConnectionError> {
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(path)?;
+        let spool = Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        };
+
+        spool.append(&SpoolRecord::Benchmark(benchmark))?;
+        debug!(path = ?path, "Created spool for benchmark {benchmark}");
+
+        Ok(spool)
+    }
+
+    pub fn append(&self, record: &SpoolRecord) -> Result<(), ConnectionError> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock();
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Read the benchmark and all runs of a spool
+///
+/// Invalid records, e.g., the incomplete last record after a crash, are skipped with a warning.
+pub fn read(path: &Path) -> Result<(ID, impl Iterator<Item = MetricsBundle>), ConnectionError> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+
+    let benchmark = match lines.next().transpose()? {
+        Some(line) => match serde_json::from_str(&line)? {
+            SpoolRecord::Benchmark(benchmark) => benchmark,
+            SpoolRecord::Run(_) => return Err(ConnectionError::InvalidSpool),
+        },
+        None => return Err(ConnectionError::InvalidSpool),
+    };
+
+    let path = path.to_path_buf();
+    let runs = lines
+        .enumerate()
+        .map_while(move |(index, line)| match line {
+            Ok(line) => Some((index, line)),
+            Err(e) => {
+                warn!(path = ?path, error = ?e, "Failed to read spool, skipping the rest: {e}");
+
+                None
+            }
+        })
+        .filter_map(|(index, line)| match serde_json::from_str(&line) {
+            Ok(SpoolRecord::Run(bundle)) => Some(bundle),
+            Ok(SpoolRecord::Benchmark(_)) => {
+                warn!(
+                    line = index + 2,
+                    "Skipping unexpected benchmark record in spool"
+                );
+
+                None
+            }
+            Err(e) => {
+                warn!(line = index + 2, error = ?e, "Skipping invalid spool record: {e}");
+
+                None
+            }
+        });
+
+    Ok((benchmark, runs))
+}
@@ -0,0 +1,49 @@
+use super::{
+    spool::{read, Spool, SpoolRecord},
+    ConnectionError, MetricsBundle, RunStatus, TestMetrics,
+};
+use crate::test_util::TempPath;
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+fn bundle(target: &str) -> MetricsBundle {
+    MetricsBundle {
+        metrics: TestMetrics::with_status(RunStatus::Timeout),
+        solver: "cadical".into(),
+        test_set: "sat".into(),
+        target: PathBuf::from(target),
+        instance: None,
+        id: None,
+    }
+}
+
+#[test]
+fn replay_spool() {
+    let path = TempPath::new("replay.spool");
+    let spool = Spool::create(&path, 3).unwrap();
+
+    spool.append(&SpoolRecord::Run(bundle("a.cnf"))).unwrap();
+    spool.append(&SpoolRecord::Run(bundle("b.cnf"))).unwrap();
+
+    // existing spools are never overwritten
+    assert!(matches!(
+        Spool::create(&path, 4),
+        Err(ConnectionError::Spool(_))
+    ));
+
+    // a crash while writing leaves an incomplete record behind
+    OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(b"{\"Run\":{\"metr")
+        .unwrap();
+
+    let (benchmark, runs) = read(&path).unwrap();
+    let targets = runs.map(|bundle| bundle.target).collect::<Vec<_>>();
+
+    assert_eq!(benchmark, 3);
+    assert_eq!(
+        targets,
+        vec![PathBuf::from("a.cnf"), PathBuf::from("b.cnf")]
+    );
+}
@@ -0,0 +1,1098 @@
+use super::{
+    util::IDMapping, ConflictRow, ConnectionAdapter, ConnectionError, MergeReport, MetricSchema,
+    MetricValue, ID,
+};
+use crate::collector::dimacs::InstanceFeatures;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+use tracing::{debug, error, info, warn};
+
+/// Row of the `benchmarks` table with all of its columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clickhouse", derive(clickhouse::Row))]
+pub struct BenchmarkRecord {
+    pub id: ID,
+    pub comment: String,
+    pub interrupted: bool,
+}
+
+/// Row of the `solvers` table with all of its columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clickhouse", derive(clickhouse::Row))]
+pub struct SolverRecord {
+    pub id: ID,
+    pub name: String,
+    pub exec: String,
+    pub params: String,
+    pub ingest: String,
+    // parameter values of a swept variant as JSON object
+    pub assignment: Option<String>,
+}
+
+/// Row of the `test_sets` table with all of its columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clickhouse", derive(clickhouse::Row))]
+pub struct TestSetRecord {
+    pub id: ID,
+    pub timeout: u32,
+    pub name: String,
+    pub params: String,
+}
+
+/// Row of the `runs` table with all of its columns in the order of `RUN_COLUMNS`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clickhouse", derive(clickhouse::Row))]
+pub struct RunRecord {
+    pub id: ID,
+    pub runtime: Option<u64>,
+    pub parse_time: u64,
+    pub satisfiable: i8,
+    pub memory_usage: u32,
+    pub restarts: u32,
+    pub conflicts: u32,
+    pub propagations: u32,
+    pub conflict_literals: u32,
+    pub number_of_variables: u32,
+    pub number_of_clauses: u32,
+    pub max_rss: u64,
+    pub user_time: u64,
+    pub system_time: u64,
+    pub voluntary_context_switches: u64,
+    pub involuntary_context_switches: u64,
+    pub minor_page_faults: u64,
+    pub major_page_faults: u64,
+    pub status: i8,
+    pub status_code: Option<i32>,
+    pub verification: i8,
+    pub target: String,
+    pub instance: Option<String>,
+    pub solver: ID,
+    pub test: ID,
+    pub benchmark: ID,
+}
+
+/// all columns of the `runs` table
+pub const RUN_COLUMNS: &str = "id, runtime, parse_time, satisfiable, memory_usage, restarts,
+    conflicts, propagations, conflict_literals, number_of_variables, number_of_clauses, max_rss,
+    user_time, system_time, voluntary_context_switches, involuntary_context_switches,
+    minor_page_faults, major_page_faults, status, status_code, verification, target, instance,
+    solver, test, benchmark";
+
+/// `RUN_COLUMNS` for reading, columns added by migrations are nullable in DuckDB
+pub const RUN_SELECT: &str = "id, runtime, parse_time, satisfiable, memory_usage, restarts,
+    conflicts, propagations, conflict_literals, number_of_variables, number_of_clauses,
+    coalesce(max_rss, 0), coalesce(user_time, 0), coalesce(system_time, 0),
+    coalesce(voluntary_context_switches, 0), coalesce(involuntary_context_switches, 0),
+    coalesce(minor_page_faults, 0), coalesce(major_page_faults, 0), coalesce(status, 0),
+    status_code, coalesce(verification, 0), target, instance, solver, test, benchmark";
+
+/// Build a `RunRecord` from a row with the columns of `RUN_SELECT`
+///
+/// SQLite and DuckDB rows share the same accessors, but not a common trait.
+macro_rules! run_record {
+    ($row:expr) => {
+        $crate::database::transfer::RunRecord {
+            id: $row.get(0)?,
+            runtime: $row.get(1)?,
+            parse_time: $row.get(2)?,
+            satisfiable: $row.get(3)?,
+            memory_usage: $row.get(4)?,
+            restarts: $row.get(5)?,
+            conflicts: $row.get(6)?,
+            propagations: $row.get(7)?,
+            conflict_literals: $row.get(8)?,
+            number_of_variables: $row.get(9)?,
+            number_of_clauses: $row.get(10)?,
+            max_rss: $row.get(11)?,
+            user_time: $row.get(12)?,
+            system_time: $row.get(13)?,
+            voluntary_context_switches: $row.get(14)?,
+            involuntary_context_switches: $row.get(15)?,
+            minor_page_faults: $row.get(16)?,
+            major_page_faults: $row.get(17)?,
+            status: $row.get(18)?,
+            status_code: $row.get(19)?,
+            verification: $row.get(20)?,
+            target: $row.get(21)?,
+            instance: $row.get(22)?,
+            solver: $row.get(23)?,
+            test: $row.get(24)?,
+            benchmark: $row.get(25)?,
+        }
+    };
+}
+pub(crate) use run_record;
+
+/// Row of a metric table with a value per column of its schema in the order of the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricRecord {
+    pub run: ID,
+    pub values: Vec<Option<MetricValue>>,
+}
+
+/// Queries of a transfer for the `InnerConnection` of SQLite and DuckDB
+///
+/// Both drivers share the same API, but not a common trait. The names used by the queries have to
+/// be imported by the adapter, which also provides `advance_sequence` for the inserted IDs, the
+/// `TABLES` query and the conversions between metric values and its own values.
+macro_rules! record_queries {
+    () => {
+        /// all benchmarks for a transfer
+        pub fn benchmarks(&self) -> Result<Vec<BenchmarkRecord>, ConnectionError> {
+            self.connection
+                .prepare(
+                    "select id, coalesce(comment, ''), coalesce(interrupted, false)
+                     from benchmarks order by id",
+                )?
+                .query_map([], |row| {
+                    Ok(BenchmarkRecord {
+                        id: row.get(0)?,
+                        comment: row.get(1)?,
+                        interrupted: row.get(2)?,
+                    })
+                })?
+                .try_fold(Vec::new(), |mut init, result| {
+                    init.push(result?);
+
+                    Ok(init)
+                })
+        }
+
+        /// all solvers for a transfer
+        pub fn solvers(&self) -> Result<Vec<SolverRecord>, ConnectionError> {
+            self.connection
+                .prepare("select id, name, exec, params, ingest, assignment from solvers order by id")?
+                .query_map([], |row| {
+                    Ok(SolverRecord {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        exec: row.get(2)?,
+                        params: row.get(3)?,
+                        ingest: row.get(4)?,
+                        assignment: row.get(5)?,
+                    })
+                })?
+                .try_fold(Vec::new(), |mut init, result| {
+                    init.push(result?);
+
+                    Ok(init)
+                })
+        }
+
+        /// all test sets for a transfer
+        pub fn test_sets(&self) -> Result<Vec<TestSetRecord>, ConnectionError> {
+            self.connection
+                .prepare("select id, timeout, name, params from test_sets order by id")?
+                .query_map([], |row| {
+                    Ok(TestSetRecord {
+                        id: row.get(0)?,
+                        timeout: row.get(1)?,
+                        name: row.get(2)?,
+                        params: row.get(3)?,
+                    })
+                })?
+                .try_fold(Vec::new(), |mut init, result| {
+                    init.push(result?);
+
+                    Ok(init)
+                })
+        }
+
+        /// all instances for a transfer
+        pub fn instances(&self) -> Result<Vec<InstanceFeatures>, ConnectionError> {
+            let mut statement = self.connection.prepare(
+                "select hash, variables, clauses, literals, header_variables, header_clauses, ratio,
+                 horn_fraction, binary_fraction, clause_lengths, min_occurrences, max_occurrences,
+                 mean_occurrences, stddev_occurrences
+                 from instances",
+            )?;
+            let mut rows = statement.query([])?;
+            let mut instances = Vec::new();
+
+            while let Some(row) = rows.next()? {
+                let clause_lengths: String = row.get(9)?;
+
+                instances.push(InstanceFeatures {
+                    hash: row.get(0)?,
+                    variables: row.get(1)?,
+                    clauses: row.get(2)?,
+                    literals: row.get(3)?,
+                    header_variables: row.get(4)?,
+                    header_clauses: row.get(5)?,
+                    ratio: row.get(6)?,
+                    horn_fraction: row.get(7)?,
+                    binary_fraction: row.get(8)?,
+                    clause_lengths: serde_json::from_str(&clause_lengths)?,
+                    min_occurrences: row.get(10)?,
+                    max_occurrences: row.get(11)?,
+                    mean_occurrences: row.get(12)?,
+                    stddev_occurrences: row.get(13)?,
+                });
+            }
+
+            Ok(instances)
+        }
+
+        /// up to `limit` runs with an ID larger than `after` in the order of their IDs
+        pub fn runs(&self, after: ID, limit: usize) -> Result<Vec<RunRecord>, ConnectionError> {
+            self.connection
+                .prepare_cached(&format!(
+                    "select {RUN_SELECT} from runs where id > ? order by id limit ?"
+                ))?
+                .query_map(params![after, limit as i64], |row| Ok(run_record!(row)))?
+                .try_fold(Vec::new(), |mut init, result| {
+                    init.push(result?);
+
+                    Ok(init)
+                })
+        }
+
+        pub fn max_run_id(&self) -> Result<ID, ConnectionError> {
+            Ok(self
+                .connection
+                .query_row("select coalesce(max(id), 0) from runs", [], |row| {
+                    row.get(0)
+                })?)
+        }
+
+        pub fn insert_benchmark(&self, benchmark: &BenchmarkRecord) -> Result<(), ConnectionError> {
+            self.connection
+                .prepare_cached("insert into benchmarks (id, comment, interrupted) values (?, ?, ?)")?
+                .execute(params![
+                    benchmark.id,
+                    benchmark.comment,
+                    benchmark.interrupted
+                ])?;
+            self.advance_sequence("seq_benchmarks", benchmark.id)?;
+
+            Ok(())
+        }
+
+        pub fn insert_solver(&self, solver: &SolverRecord) -> Result<(), ConnectionError> {
+            self.connection
+                .prepare_cached(
+                    "insert into solvers (id, name, exec, params, ingest, assignment)
+                     values (?, ?, ?, ?, ?, ?)",
+                )?
+                .execute(params![
+                    solver.id,
+                    solver.name,
+                    solver.exec,
+                    solver.params,
+                    solver.ingest,
+                    solver.assignment
+                ])?;
+            self.advance_sequence("seq_solver_id", solver.id)?;
+
+            Ok(())
+        }
+
+        pub fn insert_test_set(&self, test_set: &TestSetRecord) -> Result<(), ConnectionError> {
+            self.connection
+                .prepare_cached(
+                    "insert into test_sets (id, timeout, name, params) values (?, ?, ?, ?)",
+                )?
+                .execute(params![
+                    test_set.id,
+                    test_set.timeout,
+                    test_set.name,
+                    test_set.params
+                ])?;
+            self.advance_sequence("seq_testset", test_set.id)?;
+
+            Ok(())
+        }
+
+        /// insert runs with their IDs in a single transaction
+        pub fn insert_runs(&self, runs: &[RunRecord]) -> Result<(), ConnectionError> {
+            let tx = self.connection.unchecked_transaction()?;
+
+            {
+                let mut statement = tx.prepare_cached(&format!(
+                    "insert into runs ({RUN_COLUMNS})
+                     values (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                ))?;
+
+                for run in runs {
+                    statement.execute(params![
+                        run.id,
+                        run.runtime,
+                        run.parse_time,
+                        run.satisfiable,
+                        run.memory_usage,
+                        run.restarts,
+                        run.conflicts,
+                        run.propagations,
+                        run.conflict_literals,
+                        run.number_of_variables,
+                        run.number_of_clauses,
+                        run.max_rss,
+                        run.user_time,
+                        run.system_time,
+                        run.voluntary_context_switches,
+                        run.involuntary_context_switches,
+                        run.minor_page_faults,
+                        run.major_page_faults,
+                        run.status,
+                        run.status_code,
+                        run.verification,
+                        run.target,
+                        run.instance,
+                        run.solver,
+                        run.test,
+                        run.benchmark
+                    ])?;
+                }
+            }
+
+            tx.commit()?;
+
+            if let Some(run) = runs.last() {
+                self.advance_sequence("seq_run_id", run.id)?;
+            }
+
+            Ok(())
+        }
+
+        /// metric tables of all ingestors with their columns
+        pub fn metric_tables(&self) -> Result<BTreeMap<String, MetricSchema>, ConnectionError> {
+            let tables = self
+                .connection
+                .prepare(TABLES)?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut schemas = BTreeMap::new();
+
+            for table in tables.into_iter().filter(|table| table.ends_with("_metrics")) {
+                let mut schema = MetricSchema::new();
+                let mut statement = self
+                    .connection
+                    .prepare(&format!("select name, type from pragma_table_info('{table}')"))?;
+                let mut rows = statement.query([])?;
+
+                while let Some(row) = rows.next()? {
+                    let (name, declared): (String, String) = (row.get(0)?, row.get(1)?);
+
+                    if name == "run" {
+                        continue;
+                    }
+
+                    match metric_type(&declared, column_type) {
+                        Some(metric_type) => {
+                            schema.insert(name.into(), metric_type);
+                        }
+                        None => warn!(
+                            table = %table,
+                            "Ignoring column {name} of {table} with the unsupported type {declared}"
+                        ),
+                    }
+                }
+
+                schemas.insert(table, schema);
+            }
+
+            Ok(schemas)
+        }
+
+        /// up to `limit` rows of the metric `table` with a run ID larger than `after`
+        pub fn metric_rows(
+            &self,
+            table: &str,
+            schema: &MetricSchema,
+            after: ID,
+            limit: usize,
+        ) -> Result<Vec<MetricRecord>, ConnectionError> {
+            let mut statement = self.connection.prepare(&format!(
+                "select run{} from {} where run > ? order by run limit ?",
+                schema
+                    .keys()
+                    .map(|column| format!(", {}", quoted(column)))
+                    .collect::<String>(),
+                quoted(table)
+            ))?;
+            let mut rows = statement.query(params![after, limit as i64])?;
+            let mut records = Vec::new();
+
+            while let Some(row) = rows.next()? {
+                records.push(MetricRecord {
+                    run: row.get(0)?,
+                    values: (1..=schema.len())
+                        .map(|index| row.get::<_, Value>(index).map(metric_from_value))
+                        .collect::<Result<_, _>>()?,
+                });
+            }
+
+            Ok(records)
+        }
+
+        /// largest run ID of the metric `table`, 0 if it is empty
+        pub fn max_metric_run(&self, table: &str) -> Result<ID, ConnectionError> {
+            Ok(self.connection.query_row(
+                &format!("select coalesce(max(run), 0) from {}", quoted(table)),
+                [],
+                |row| row.get(0),
+            )?)
+        }
+
+        /// insert rows of the metric `table` in a single transaction
+        pub fn insert_metric_rows(
+            &self,
+            table: &str,
+            schema: &MetricSchema,
+            rows: &[MetricRecord],
+        ) -> Result<(), ConnectionError> {
+            let tx = self.connection.unchecked_transaction()?;
+
+            {
+                let mut statement = tx.prepare_cached(&metric_insert(table, schema.keys()))?;
+
+                for row in rows {
+                    statement.execute(params_from_iter(
+                        iter::once(Value::from(row.run)).chain(
+                            row.values
+                                .iter()
+                                .map(|value| value.as_ref().map_or(Value::Null, metric_value)),
+                        ),
+                    ))?;
+                }
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        }
+
+        /// all recorded answer conflicts with their benchmark
+        pub fn conflicts(&self) -> Result<Vec<(ID, ConflictRow)>, ConnectionError> {
+            self.connection
+                .prepare(
+                    "select benchmark, run, instance, satisfiable from answer_conflicts
+                     order by run",
+                )?
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        ConflictRow {
+                            run: row.get(1)?,
+                            instance: row.get(2)?,
+                            satisfiable: row.get(3)?,
+                        },
+                    ))
+                })?
+                .try_fold(Vec::new(), |mut init, result| {
+                    init.push(result?);
+
+                    Ok(init)
+                })
+        }
+    };
+}
+pub(crate) use record_queries;
+
+/// Progress of a transfer, saved after every step to resume interrupted transfers
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TransferState {
+    pub benchmarks: IDMapping,
+    pub solvers: IDMapping,
+    pub test_sets: IDMapping,
+    pub instances: bool,
+    // added to the source ID of every run, fixed once the first run was transferred
+    pub run_offset: Option<ID>,
+}
+
+impl TransferState {
+    /// load the state of a previous transfer, a missing file starts a new transfer
+    pub fn load(path: &Path) -> Result<Self, ConnectionError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let state: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        info!(path = ?path, "Resuming transfer");
+
+        Ok(state)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConnectionError> {
+        // the state is replaced atomically, a crash never leaves a truncated state behind
+        let temporary = path.with_extension("tmp");
+
+        fs::write(&temporary, serde_json::to_vec(self)?)?;
+        fs::rename(temporary, path)?;
+
+        Ok(())
+    }
+}
+
+/// Entries that are transferred with an ID mapping
+pub(super) trait Entry: Clone {
+    fn id(&self) -> ID;
+    fn with_id(&self, id: ID) -> Self;
+    /// entries equal in all columns but the ID are reused instead of transferred
+    fn same(&self, other: &Self) -> bool;
+}
+
+impl Entry for BenchmarkRecord {
+    fn id(&self) -> ID {
+        self.id
+    }
+
+    fn with_id(&self, id: ID) -> Self {
+        Self { id, ..self.clone() }
+    }
+
+    // benchmarks are never reused, even with identical comments
+    fn same(&self, _: &Self) -> bool {
+        false
+    }
+}
+
+impl Entry for SolverRecord {
+    fn id(&self) -> ID {
+        self.id
+    }
+
+    fn with_id(&self, id: ID) -> Self {
+        Self { id, ..self.clone() }
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.exec == other.exec
+            && self.params == other.params
+            && self.ingest == other.ingest
+            && self.assignment == other.assignment
+    }
+}
+
+impl Entry for TestSetRecord {
+    fn id(&self) -> ID {
+        self.id
+    }
+
+    fn with_id(&self, id: ID) -> Self {
+        Self { id, ..self.clone() }
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self.name == other.name && self.timeout == other.timeout && self.params == other.params
+    }
+}
+
+/// Map every source entry to a target ID and return the entries that have to be inserted
+///
+/// IDs are preserved unless the target uses them already, mapped entries that are missing in the
+/// target, e.g., after a crash, are inserted again. Returns the entries and the number of reused
+/// target entries.
+pub(super) fn plan<T: Entry>(
+    source: &[T],
+    target: &[T],
+    mapping: &mut IDMapping,
+) -> (Vec<T>, usize) {
+    let mut taken = target.iter().map(T::id).collect::<BTreeSet<_>>();
+    taken.extend(mapping.values());
+
+    // remapped entries never collide with source IDs that are preserved later on
+    let mut next = taken
+        .iter()
+        .copied()
+        .chain(source.iter().map(T::id))
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let (mut inserts, mut reused) = (Vec::new(), 0);
+
+    for entry in source {
+        if let Some(id) = mapping.get(&entry.id()) {
+            if !target.iter().any(|existing| existing.id() == *id) {
+                inserts.push(entry.with_id(*id));
+            }
+
+            continue;
+        }
+
+        if let Some(existing) = target.iter().find(|existing| existing.same(entry)) {
+            mapping.insert(entry.id(), existing.id());
+            reused += 1;
+
+            continue;
+        }
+
+        let id = if taken.contains(&entry.id()) {
+            next += 1;
+
+            next - 1
+        } else {
+            entry.id()
+        };
+
+        taken.insert(id);
+        mapping.insert(entry.id(), id);
+        inserts.push(entry.with_id(id));
+    }
+
+    (inserts, reused)
+}
+
+/// number of runs that are copied at once while merging
+const MERGE_CHUNK_SIZE: usize = 10000;
+
+/// Transfer all benchmarks, solvers, test sets, instances and runs from `source` to `target`
+///
+/// The progress is kept in `state_path`, an interrupted transfer continues where it stopped when
+/// started again with the same state. Runs are read and written in chunks of `chunk_size` runs.
+pub fn transfer(
+    source: &ConnectionAdapter,
+    target: &mut ConnectionAdapter,
+    state_path: &Path,
+    chunk_size: usize,
+) -> Result<MergeReport, ConnectionError> {
+    let mut state = TransferState::load(state_path)?;
+
+    copy(source, target, &mut state, Some(state_path), chunk_size)
+}
+
+/// Merge all entries of `source` into `target` without keeping the progress
+///
+/// Solvers and test sets with identical attributes are reused, all other entries keep their IDs
+/// unless `target` uses them already.
+pub fn merge(
+    source: &ConnectionAdapter,
+    target: &mut ConnectionAdapter,
+) -> Result<MergeReport, ConnectionError> {
+    copy(
+        source,
+        target,
+        &mut TransferState::default(),
+        None,
+        MERGE_CHUNK_SIZE,
+    )
+}
+
+/// copy all entries of `source` to `target`, the state is saved to `state_path` if given
+fn copy(
+    source: &ConnectionAdapter,
+    target: &mut ConnectionAdapter,
+    state: &mut TransferState,
+    state_path: Option<&Path>,
+    chunk_size: usize,
+) -> Result<MergeReport, ConnectionError> {
+    // the source is only read, it has to be upgraded by `satan-runner migrate` beforehand
+    source.check_schema()?;
+    target.apply_schema()?;
+
+    let save = |state: &TransferState| match state_path {
+        Some(path) => state.save(path),
+        None => Ok(()),
+    };
+    let mut report = MergeReport::default();
+
+    // the mapping is saved before inserting, entries can't be inserted twice with different IDs
+    let (benchmarks, _) = plan(
+        &source.benchmarks()?,
+        &target.benchmarks()?,
+        &mut state.benchmarks,
+    );
+    save(state)?;
+    for benchmark in benchmarks.iter() {
+        target.insert_benchmark(benchmark)?;
+    }
+    report.benchmarks = benchmarks.len();
+    info!("Transferred {} benchmarks", benchmarks.len());
+
+    let (solvers, reused) = plan(&source.solvers()?, &target.solvers()?, &mut state.solvers);
+    save(state)?;
+    for solver in solvers.iter() {
+        target.insert_solver(solver)?;
+    }
+    (report.solvers_created, report.solvers_reused) = (solvers.len(), reused);
+    info!("Transferred {} solvers ({reused} reused)", solvers.len());
+
+    let (test_sets, reused) = plan(
+        &source.test_sets()?,
+        &target.test_sets()?,
+        &mut state.test_sets,
+    );
+    save(state)?;
+    for test_set in test_sets.iter() {
+        target.insert_test_set(test_set)?;
+    }
+    (report.test_sets_created, report.test_sets_reused) = (test_sets.len(), reused);
+    info!(
+        "Transferred {} test sets ({reused} reused)",
+        test_sets.len()
+    );
+
+    if !state.instances {
+        // instances with a known hash are ignored by all adapters
+        for instance in source.instances()? {
+            target.store_instance(&instance)?;
+            report.instances += 1;
+        }
+
+        state.instances = true;
+        save(state)?;
+        info!("Transferred {} instances", report.instances);
+    }
+
+    let offset = match state.run_offset {
+        Some(offset) => offset,
+        None => {
+            let first = source.runs(ID::MIN, 1)?.first().map_or(0, |run| run.id);
+            // runs keep their ID if it is larger than all runs of the target
+            let offset = (target.max_run_id()? - first + 1).max(0);
+
+            state.run_offset = Some(offset);
+            save(state)?;
+
+            offset
+        }
+    };
+
+    // chunks are inserted atomically and in order, all runs up to the largest ID are complete
+    let mut last = target.max_run_id()? - offset;
+
+    loop {
+        let mut runs = source.runs(last, chunk_size)?;
+
+        let Some(run) = runs.last() else {
+            break;
+        };
+        last = run.id;
+
+        for run in runs.iter_mut() {
+            run.id += offset;
+            run.benchmark = remap(&state.benchmarks, "benchmarks", run.benchmark)?;
+            run.solver = remap(&state.solvers, "solvers", run.solver)?;
+            run.test = remap(&state.test_sets, "test_sets", run.test)?;
+        }
+
+        target.insert_runs(&runs)?;
+        report.runs += runs.len();
+        debug!("Transferred {} runs up to {last}", runs.len());
+    }
+
+    info!("Transferred {} runs", report.runs);
+
+    // metric rows reference their runs and are copied once all runs are complete
+    for (table, schema) in source.metric_tables()? {
+        target.create_metric_table(&table, &schema)?;
+
+        let mut last = target.max_metric_run(&table)? - offset;
+        let mut transferred = 0;
+
+        loop {
+            let mut rows = source.metric_rows(&table, &schema, last, chunk_size)?;
+
+            let Some(row) = rows.last() else {
+                break;
+            };
+            last = row.run;
+
+            for row in rows.iter_mut() {
+                row.run += offset;
+            }
+
+            target.insert_metric_rows(&table, &schema, &rows)?;
+            transferred += rows.len();
+        }
+
+        report.metrics += transferred;
+        info!(table = %table, "Transferred {transferred} rows of {table}");
+    }
+
+    // conflicts are replaced per benchmark, a resumed transfer stores them again
+    let mut conflicts = BTreeMap::<ID, Vec<ConflictRow>>::new();
+    for (benchmark, mut conflict) in source.conflicts()? {
+        conflict.run += offset;
+        conflicts
+            .entry(remap(&state.benchmarks, "benchmarks", benchmark)?)
+            .or_default()
+            .push(conflict);
+    }
+
+    if !target.records_conflicts() {
+        if !conflicts.is_empty() {
+            warn!("The target doesn't record answer conflicts, check the benchmarks again after transferring them to a SQLite or DuckDB database");
+        }
+    } else {
+        for (benchmark, conflicts) in conflicts.iter() {
+            target.store_conflicts(*benchmark, conflicts)?;
+            report.conflicts += conflicts.len();
+        }
+
+        info!("Transferred {} answer conflicts", report.conflicts);
+    }
+
+    Ok(report)
+}
+
+fn remap(mapping: &IDMapping, table: &str, id: ID) -> Result<ID, ConnectionError> {
+    match mapping.get(&id) {
+        Some(id) => Ok(*id),
+        None => {
+            error!(table = table, id = id, "Found dangling reference");
+
+            Err(ConnectionError::DanglingReference(table.to_owned()))
+        }
+    }
+}
+
+impl ConnectionAdapter {
+    /// Create the schema of a transfer target, existing databases are upgraded
+    pub fn apply_schema(&mut self) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.migrate().map(|_| ()),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.migrate().map(|_| ()),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.apply_schema(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn benchmarks(&self) -> Result<Vec<BenchmarkRecord>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().benchmarks(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().benchmarks(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.benchmarks(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn solvers(&self) -> Result<Vec<SolverRecord>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().solvers(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().solvers(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.solvers(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn test_sets(&self) -> Result<Vec<TestSetRecord>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().test_sets(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().test_sets(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.test_sets(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn instances(&self) -> Result<Vec<InstanceFeatures>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().instances(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().instances(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.instances(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Read up to `limit` runs with an ID larger than `after` in the order of their IDs
+    pub fn runs(&self, after: ID, limit: usize) -> Result<Vec<RunRecord>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().runs(after, limit),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().runs(after, limit),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.runs(after, limit),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Largest ID of all runs, 0 if there aren't any
+    pub fn max_run_id(&self) -> Result<ID, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().max_run_id(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().max_run_id(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.max_run_id(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn insert_benchmark(&self, benchmark: &BenchmarkRecord) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().insert_benchmark(benchmark),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().insert_benchmark(benchmark),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.insert_benchmark(benchmark),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn insert_solver(&self, solver: &SolverRecord) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().insert_solver(solver),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().insert_solver(solver),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.insert_solver(solver),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    pub fn insert_test_set(&self, test_set: &TestSetRecord) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().insert_test_set(test_set),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().insert_test_set(test_set),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.insert_test_set(test_set),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Metric tables of all ingestors with their columns
+    pub fn metric_tables(&self) -> Result<BTreeMap<String, MetricSchema>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().metric_tables(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().metric_tables(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.metric_tables(),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Create the metric `table` unless it exists and add the missing columns of `schema`
+    pub fn create_metric_table(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+    ) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => {
+                shared_connection.lock().create_metric_table(table, schema)
+            }
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => {
+                shared_connection.lock().create_metric_table(table, schema)
+            }
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.create_metric_table(table, schema),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Read up to `limit` rows of the metric `table` with a run ID larger than `after` in the
+    /// order of their runs
+    pub fn metric_rows(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+        after: ID,
+        limit: usize,
+    ) -> Result<Vec<MetricRecord>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection
+                .lock()
+                .metric_rows(table, schema, after, limit),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection
+                .lock()
+                .metric_rows(table, schema, after, limit),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.metric_rows(table, schema, after, limit),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Largest run ID of the metric `table`, 0 if it is empty
+    pub fn max_metric_run(&self, table: &str) -> Result<ID, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().max_metric_run(table),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().max_metric_run(table),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.max_metric_run(table),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Insert rows of the metric `table` atomically
+    pub fn insert_metric_rows(
+        &self,
+        table: &str,
+        schema: &MetricSchema,
+        rows: &[MetricRecord],
+    ) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection
+                .lock()
+                .insert_metric_rows(table, schema, rows),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection
+                .lock()
+                .insert_metric_rows(table, schema, rows),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.insert_metric_rows(table, schema, rows),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// All recorded answer conflicts with their benchmark
+    pub fn conflicts(&self) -> Result<Vec<(ID, ConflictRow)>, ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().conflicts(),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().conflicts(),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse { .. } => Ok(Vec::new()),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+
+    /// Whether answer conflicts are recorded, ClickHouse databases don't contain them
+    pub fn records_conflicts(&self) -> bool {
+        #[cfg(feature = "clickhouse")]
+        if let Self::ClickHouse { .. } = self {
+            return false;
+        }
+
+        true
+    }
+
+    /// Insert runs with their IDs atomically
+    pub fn insert_runs(&self, runs: &[RunRecord]) -> Result<(), ConnectionError> {
+        match self {
+            #[cfg(feature = "duckdb")]
+            Self::DuckDB(shared_connection) => shared_connection.lock().insert_runs(runs),
+            #[cfg(feature = "rusqlite")]
+            Self::SQLite(shared_connection) => shared_connection.lock().insert_runs(runs),
+            #[cfg(feature = "clickhouse")]
+            Self::ClickHouse(connection) => connection.insert_runs(runs),
+            Self::Batched { .. } | Self::Delayed { .. } => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use super::{
+    transfer::{plan, SolverRecord},
+    util::IDMapping,
+};
+
+fn solver(id: i32, name: &str) -> SolverRecord {
+    SolverRecord {
+        id,
+        name: name.to_owned(),
+        exec: format!("/usr/bin/{name}"),
+        params: String::new(),
+        ingest: "null".to_owned(),
+        assignment: None,
+    }
+}
+
+#[test]
+fn plan_solvers() {
+    let source = vec![
+        solver(1, "cadical"),
+        solver(2, "kissat"),
+        solver(3, "minisat"),
+    ];
+    let target = vec![solver(1, "kissat"), solver(4, "glucose")];
+    let mut mapping = IDMapping::new();
+
+    let (inserts, reused) = plan(&source, &target, &mut mapping);
+
+    // identical solvers are reused, taken IDs are replaced by IDs unused in source and target
+    assert_eq!(reused, 1);
+    assert_eq!(inserts, vec![solver(5, "cadical"), solver(3, "minisat")]);
+    assert_eq!(mapping, IDMapping::from([(1, 5), (2, 1), (3, 3)]));
+
+    // a resumed transfer inserts planned solvers missing in the target again
+    let (inserts, reused) = plan(
+        &source,
+        &[solver(1, "kissat"), solver(5, "cadical")],
+        &mut mapping,
+    );
+
+    assert_eq!(reused, 0);
+    assert_eq!(inserts, vec![solver(3, "minisat")]);
+    assert_eq!(mapping, IDMapping::from([(1, 5), (2, 1), (3, 3)]));
+}
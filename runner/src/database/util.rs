@@ -17,9 +17,141 @@
 ///                              │          ┌──────────┐
 ///                              └─────────►│Benchmarks│
 ///                                         └──────────┘
+use super::{ConnectionError, MetricSchema, MetricType, MetricValue, Metrics, ID};
+use crate::config::SolverConfig;
 use cowstr::CowStr;
+use itertools::Itertools;
 use std::collections::BTreeMap;
 
 pub type IDMap = BTreeMap<CowStr, i32>;
 
+/// mapping of IDs between two databases, e.g., while merging
+pub type IDMapping = BTreeMap<i32, i32>;
 
+/// ID of the registered solver or test set `name` of `table`
+///
+/// Spooled runs may reference entries that were removed from the config since.
+pub fn registered_id(map: &IDMap, table: &str, name: &CowStr) -> Result<ID, ConnectionError> {
+    map.get(name)
+        .copied()
+        .ok_or_else(|| ConnectionError::DanglingReference(format!("{table} ({name})")))
+}
+
+/// version of a database schema, databases created before versioning are at version 1
+pub type SchemaVersion = i32;
+
+/// Single step of a schema migration
+#[derive(Debug, Clone, Copy)]
+pub enum Migration {
+    Execute(&'static str),
+    /// add a column unless it exists already, databases created before versioning may contain
+    /// some of the migrated columns
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+/// Tables with the solver specific metrics of the ingestors of all configured solvers
+///
+/// Each ingestor that declares metrics gets a `{ingestor}_metrics` table with a column per metric
+/// that references the run by its ID.
+#[derive(Debug, Default)]
+pub struct MetricTables {
+    // ingestor of each solver with declared metrics
+    solvers: BTreeMap<CowStr, CowStr>,
+    schemas: BTreeMap<CowStr, MetricSchema>,
+}
+
+impl MetricTables {
+    pub fn new(config: &SolverConfig) -> Self {
+        let mut tables = Self::default();
+
+        for (name, solver) in config.solvers.iter() {
+            let schema = match config.ingest.get(&solver.ingest) {
+                Some(ingestor) => ingestor.metrics(),
+                None => continue,
+            };
+
+            if !schema.is_empty() {
+                tables.solvers.insert(name.clone(), solver.ingest.clone());
+                tables.schemas.insert(solver.ingest.clone(), schema);
+            }
+        }
+
+        tables
+    }
+
+    /// all tables with their declared metrics
+    pub fn iter(&self) -> impl Iterator<Item = (String, &MetricSchema)> {
+        self.schemas
+            .iter()
+            .map(|(ingestor, schema)| (metric_table(ingestor), schema))
+    }
+
+    /// Insert statement and its values for the declared metrics of a run of `solver`
+    ///
+    /// The ID of the run has to be bound to the first parameter. Returns `None` if the run didn't
+    /// report any declared metrics, e.g., as its ingestor failed.
+    pub fn insert<'a>(
+        &self,
+        solver: &CowStr,
+        metrics: &'a Metrics,
+    ) -> Option<(String, Vec<&'a MetricValue>)> {
+        let ingestor = self.solvers.get(solver)?;
+        let schema = self.schemas.get(ingestor)?;
+        let (columns, values): (Vec<_>, Vec<_>) = metrics
+            .iter()
+            // spooled runs may contain metrics that aren't declared anymore
+            .filter(|(name, _)| schema.contains_key(*name))
+            .unzip();
+
+        if columns.is_empty() {
+            return None;
+        }
+
+        Some((
+            metric_insert(&metric_table(ingestor), columns.into_iter()),
+            values,
+        ))
+    }
+}
+
+/// name of the table with the solver specific metrics of `ingestor`
+pub fn metric_table(ingestor: &str) -> String {
+    format!("{ingestor}_metrics")
+}
+
+/// Quote a table or column name, metric names could be keywords otherwise
+///
+/// Ingestor and metric names are restricted to letters, digits and underscores by the preflight
+/// checks, so they never contain quotes.
+pub fn quoted(name: &str) -> String {
+    format!("\"{name}\"")
+}
+
+/// insert statement for the `columns` of a metric table, the run ID is bound to the first parameter
+pub fn metric_insert<'a>(table: &str, columns: impl Iterator<Item = &'a CowStr>) -> String {
+    let columns = columns.collect_vec();
+
+    format!(
+        "insert into {} (run{}) values (?{})",
+        quoted(table),
+        columns
+            .iter()
+            .map(|column| format!(", {}", quoted(column)))
+            .join(""),
+        ", ?".repeat(columns.len())
+    )
+}
+
+/// type of a metric column that was declared with `column_type` of an adapter
+pub fn metric_type(
+    declared: &str,
+    column_type: fn(MetricType) -> &'static str,
+) -> Option<MetricType> {
+    [MetricType::Int, MetricType::Float, MetricType::String]
+        .into_iter()
+        .find(|metric_type| column_type(*metric_type).eq_ignore_ascii_case(declared))
+}
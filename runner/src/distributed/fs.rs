@@ -4,21 +4,31 @@ use std::{
     ffi::OsString,
     ops::{Deref, DerefMut},
     path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
 };
 use tracing::{debug, error};
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 /// A path that will rename the underlying object from PROCESSING_PREFIX to DONE_PREFIX when
 /// dropped
+///
+/// Interrupted paths are renamed back to their original name instead, so other nodes or a later
+/// run pick them up again.
 pub struct WrappedPath {
     path: PathBuf,
+    interrupted: AtomicBool,
 }
 
 impl Drop for WrappedPath {
     fn drop(&mut self) {
         // This is buggy, I think
         let file_name = self.path.file_name().unwrap().to_os_string();
-        let done_file_name = reprefix(&file_name, &PROCESSING_PREFIX, DONE_PREFIX.clone());
+        let new_prefix = if self.interrupted.load(Ordering::SeqCst) {
+            OsString::new()
+        } else {
+            DONE_PREFIX.clone()
+        };
+        let done_file_name = reprefix(&file_name, &PROCESSING_PREFIX, new_prefix);
 
         let mut done_file_path = self.path.clone();
         done_file_path.set_file_name(done_file_name);
@@ -32,7 +42,15 @@ impl Drop for WrappedPath {
 
 impl WrappedPath {
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            interrupted: AtomicBool::new(false),
+        }
+    }
+
+    /// restore the original name on drop, not all runs on this path were finished
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
     }
 }
 
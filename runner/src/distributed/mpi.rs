@@ -1,10 +1,68 @@
-use crate::collector::Collector;
+use crate::collector::{Collector, PathValue};
+#[cfg(feature = "mpi")]
+use crate::{
+    collector::CollectorMap,
+    config::SolverConfig,
+    database::{
+        instance_key, CompletedRuns, ConnectionAdapter, MetricsBundle, RunStatus, Verification,
+    },
+    executors::{run_solver, ExecutorError, InstanceRegistry},
+    ingest::IngestorMap,
+    shutdown,
+};
+#[cfg(feature = "mpi")]
+use cowstr::CowStr;
+#[cfg(feature = "mpi")]
+use mpi::{topology::SimpleCommunicator, traits::*, Rank};
+#[cfg(feature = "mpi")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mpi")]
+use std::{
+    collections::{BTreeSet, VecDeque},
+    path::PathBuf,
+};
+#[cfg(feature = "mpi")]
+use tracing::{debug, error, info, span, warn, Level};
 
+/// rank of the process that owns the task list and the database connection
+#[cfg(feature = "mpi")]
+pub const COORDINATOR: Rank = 0;
+
+/// A single unit of work, i.e., one iteration of a solver on a test file
+#[cfg(feature = "mpi")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub test_set: CowStr,
+    pub solver: CowStr,
+    pub target: PathBuf,
+    pub instance: Option<CowStr>,
+    pub iteration: usize,
+}
+
+/// Messages exchanged between the coordinator and the workers
+///
+/// Workers always send `Ready`, `Result` or `Failed` and are answered with either `Task` or `Done`.
+#[cfg(feature = "mpi")]
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Message {
+    Ready,
+    Result(MetricsBundle),
+    Failed(String),
+    Task(Task),
+    Done,
+}
+
+/// Coordinates work between MPI ranks
+///
+/// The coordinator rank owns the collected tasks and the database connection, all other ranks
+/// request tasks on demand and ship their results back.
+#[cfg(feature = "mpi")]
 #[derive(Debug)]
 pub struct MPICoordinator {
-    pub is_master: bool,
+    world: SimpleCommunicator,
 }
 
+/// Collector that is only iterated on the coordinator rank
 #[derive(Debug, Clone)]
 pub struct MPICollector {
     inner: Box<Collector>,
@@ -18,5 +76,299 @@ impl MPICollector {
     }
 }
 
+impl Iterator for MPICollector {
+    type Item = PathValue;
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+/// Worker side storage that ships results to the coordinator instead of a database
+#[cfg(feature = "mpi")]
 #[derive(Debug)]
-pub struct MPIStorage {}
+pub struct MPIStorage<'a> {
+    world: &'a SimpleCommunicator,
+}
+
+#[cfg(feature = "mpi")]
+fn send(world: &SimpleCommunicator, rank: Rank, message: &Message) -> Result<(), ExecutorError> {
+    let buffer = serde_json::to_vec(message)?;
+    world.process_at_rank(rank).send(&buffer[..]);
+
+    Ok(())
+}
+
+#[cfg(feature = "mpi")]
+impl MPIStorage<'_> {
+    /// send `message` to the coordinator and wait for the next instruction
+    pub fn exchange(&self, message: &Message) -> Result<Message, ExecutorError> {
+        send(self.world, COORDINATOR, message)?;
+        let (buffer, _status) = self.world.process_at_rank(COORDINATOR).receive_vec::<u8>();
+
+        Ok(serde_json::from_slice(&buffer)?)
+    }
+}
+
+#[cfg(feature = "mpi")]
+impl MPICoordinator {
+    pub fn new(world: SimpleCommunicator) -> Self {
+        Self { world }
+    }
+
+    pub fn is_coordinator(&self) -> bool {
+        self.world.rank() == COORDINATOR
+    }
+
+    /// ranks of all workers
+    fn workers(&self) -> BTreeSet<Rank> {
+        (0..self.world.size())
+            .filter(|rank| *rank != COORDINATOR)
+            .collect()
+    }
+
+    /// answer the next message of every worker in `workers` with `Done`, workers wait for a reply
+    /// until they are released
+    fn release(&self, mut workers: BTreeSet<Rank>) {
+        while !workers.is_empty() {
+            let (_, status) = self.world.any_process().receive_vec::<u8>();
+            let worker = status.source_rank();
+
+            if let Err(e) = send(&self.world, worker, &Message::Done) {
+                error!(error = ?e, worker = worker, "Failed to release worker: {e}");
+            }
+
+            workers.remove(&worker);
+        }
+    }
+
+    /// release all workers without handing out any tasks, e.g., if the coordinator failed to
+    /// start
+    pub fn abort(&self) {
+        self.release(self.workers());
+    }
+
+    /// hand out all tasks to the workers and store their results, this is only run on the
+    /// coordinator rank
+    pub fn coordinate(
+        self,
+        connection: ConnectionAdapter,
+        config: &SolverConfig,
+        collectors: CollectorMap,
+        completed: CompletedRuns,
+    ) -> Result<(), ExecutorError> {
+        let span = span!(Level::INFO, "mpi-coordinator");
+        let _enter = span.enter();
+
+        let mut workers = self.workers();
+
+        if workers.is_empty() {
+            error!("Coordinated execution requires at least two MPI ranks, e.g., mpirun -np 2");
+
+            return Err(ExecutorError::NoWorkers);
+        }
+
+        let mut tasks = VecDeque::new();
+        let instances = InstanceRegistry::default();
+
+        for (name, collector) in collectors {
+            let set = config.tests.get(&name).unwrap();
+
+            for path in collector {
+                let instance = instances.register(&path, &connection);
+
+                for solver in set.solvers.iter() {
+                    let completed = completed
+                        .get(&(
+                            name.clone(),
+                            solver.clone(),
+                            instance_key(instance.as_ref(), &path),
+                        ))
+                        .copied()
+                        .unwrap_or_default();
+
+                    for iteration in completed..set.iterations {
+                        tasks.push_back(Task {
+                            test_set: name.clone(),
+                            solver: solver.clone(),
+                            target: path.to_path_buf(),
+                            instance: instance.clone(),
+                            iteration,
+                        });
+                    }
+                }
+            }
+        }
+
+        let total = tasks.len();
+        let (mut processed, mut errors, mut wrong_answers) = (0, 0, 0);
+
+        let mut failure = None;
+
+        info!("Distributing {total} tasks to {} workers", workers.len());
+
+        while !workers.is_empty() {
+            let (buffer, status) = self.world.any_process().receive_vec::<u8>();
+            let worker = status.source_rank();
+
+            // the worker waits for a reply regardless
+            let message = serde_json::from_slice(&buffer)
+                .unwrap_or_else(|e| Message::Failed(format!("Invalid message: {e}")));
+
+            match message {
+                Message::Ready => debug!(worker = worker, "Worker is ready"),
+                Message::Result(bundle) => {
+                    processed += 1;
+
+                    if matches!(
+                        bundle.metrics.status,
+                        RunStatus::SpawnError | RunStatus::IngestError
+                    ) {
+                        errors += 1;
+                    }
+
+                    if bundle.metrics.verification == Verification::Invalid {
+                        wrong_answers += 1;
+                    }
+
+                    match connection.store(
+                        bundle.metrics,
+                        bundle.solver,
+                        bundle.test_set,
+                        &bundle.target,
+                        bundle.instance,
+                    ) {
+                        Ok(id) => debug!(worker = worker, id = id, "Saved run {id}"),
+                        Err(e) => {
+                            error!(error = ?e, "Failed to insert metric: {e}");
+                            errors += 1;
+                        }
+                    }
+                }
+                Message::Failed(e) => {
+                    processed += 1;
+                    errors += 1;
+
+                    error!(worker = worker, "Worker failed to execute run: {e}");
+                }
+                message => {
+                    warn!(worker = worker, message = ?message, "Received unexpected message")
+                }
+            }
+
+            // workers are released once a shutdown was requested
+            let next = if shutdown::requested() {
+                None
+            } else {
+                tasks.pop_front()
+            };
+
+            let reply = match next {
+                Some(task) => Message::Task(task),
+                None => {
+                    workers.remove(&worker);
+
+                    Message::Done
+                }
+            };
+
+            if let Err(e) = send(&self.world, worker, &reply) {
+                error!(error = ?e, worker = worker, "Failed to reply to worker: {e}");
+
+                workers.remove(&worker);
+                failure = Some(e);
+
+                break;
+            }
+
+            info!("Done with {processed}/{total} [errors: {errors}]");
+        }
+
+        // the remaining workers are only left after a failure
+        self.release(workers);
+
+        let interrupted = shutdown::requested() || failure.is_some();
+
+        connection.finish(interrupted)?;
+
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        if interrupted {
+            warn!(
+                "Execution was interrupted with {} tasks left, continue it with --resume",
+                tasks.len()
+            );
+        }
+
+        if errors > 0 {
+            warn!("{errors} errors were encountered during execution, consult the logs for more information")
+        }
+
+        if wrong_answers > 0 {
+            error!("{wrong_answers} runs produced WRONG ANSWERS, they are stored with verification = -1")
+        }
+
+        Ok(())
+    }
+
+    /// request and execute tasks until the coordinator has no work left, this is run on all ranks
+    /// except the coordinator
+    pub fn work(self, config: &SolverConfig, ingestors: &IngestorMap) -> Result<(), ExecutorError> {
+        let span = span!(Level::INFO, "mpi-worker", rank = self.world.rank());
+        let _enter = span.enter();
+
+        let storage = MPIStorage { world: &self.world };
+        let mut message = Message::Ready;
+
+        loop {
+            match storage.exchange(&message)? {
+                Message::Task(task) => {
+                    debug!(task = ?task, "Received task");
+
+                    let solver = config.solvers.get(&task.solver).unwrap();
+                    let set = config.tests.get(&task.test_set).unwrap();
+                    let ingestor = ingestors.get(&solver.ingest).unwrap();
+
+                    message = match run_solver(solver, set, &task.target, ingestor) {
+                        // mpirun forwards signals to all ranks, the coordinator stops handing out tasks as well
+                        Err(ExecutorError::Interrupted) => {
+                            info!(task = ?task, "Interrupted run");
+
+                            Message::Ready
+                        }
+                        Ok(metrics) => Message::Result(MetricsBundle {
+                            metrics,
+                            solver: task.solver,
+                            test_set: task.test_set,
+                            target: task.target,
+                            instance: task.instance,
+                            id: None,
+                        }),
+                        Err(e) => Message::Failed(format!(
+                            "{}/{}/{}: {e}",
+                            task.test_set,
+                            task.solver,
+                            task.target.to_string_lossy()
+                        )),
+                    };
+                }
+                Message::Done => break,
+                unexpected => {
+                    warn!(message = ?unexpected, "Received unexpected message");
+
+                    message = Message::Ready;
+                }
+            }
+        }
+
+        info!("No tasks left, shutting down worker");
+
+        Ok(())
+    }
+}
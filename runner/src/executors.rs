@@ -1,28 +1,55 @@
+pub mod pipe;
+pub mod verify;
+#[cfg(test)]
+mod verify_test;
+
 use crate::{
-    collector::CollectorMap,
-    config::{ExecutorConfig, SolverConfig},
-    database::{ConnectionAdapter, ConnectionError, TestMetrics},
+    collector::{dimacs, CollectorMap},
+    config::{ExecutorConfig, Solver, SolverConfig, TestSet},
+    database::{
+        instance_key, CompletedRuns, ConnectionAdapter, ConnectionError, ResourceUsage, RunStatus,
+        TestMetrics, Verification,
+    },
     distributed::SynchronizationTypes,
-    ingest::{IngestorError, IngestorMap, RunOutput},
+    ingest::{IngestorError, IngestorMap, Ingestors, RunOutput},
+    shutdown,
 };
 use affinity::{get_core_num, set_thread_affinity};
 use cowstr::CowStr;
 use itertools::iproduct;
+use nix::{
+    errno::Errno,
+    libc::{self, pid_t, rusage},
+    sys::{
+        resource::{setrlimit, Resource},
+        signal::{killpg, Signal},
+        wait::{waitpid, WaitPidFlag, WaitStatus},
+    },
+    unistd::Pid,
+};
+use parking_lot::Mutex;
+use pipe::{Captured, PipeReader};
 use rayon::{prelude::*, ThreadPoolBuilder};
 use std::{
+    collections::BTreeMap,
     ffi::OsStr,
-    io::Read,
-    process::{Command, Stdio},
+    io,
+    mem::MaybeUninit,
+    os::unix::process::{CommandExt, ExitStatusExt},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
-        Arc,
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Once, OnceLock,
     },
+    thread,
     time::{Duration, Instant},
 };
 use thiserror::Error;
 use tracing::{debug, error, info, instrument, span, warn, Level};
 use tracing_unwrap::{OptionExt, ResultExt};
-use wait_timeout::ChildExt;
+use verify::{ProofFile, PROOF_PLACEHOLDER};
 
 #[derive(Error, Debug)]
 pub enum ExecutorError {
@@ -32,10 +59,66 @@ pub enum ExecutorError {
     ConnectionError(#[from] ConnectionError),
     #[error("Failed to build collector globs")]
     GlobError(#[from] globset::Error),
+    #[error("Failed to supervise solver")]
+    IOError(#[from] std::io::Error),
+    #[error("Run was interrupted by a shutdown")]
+    Interrupted,
+    #[cfg(feature = "mpi")]
+    #[error("Failed to exchange message with MPI rank")]
+    MessageError(#[from] serde_json::Error),
+    #[cfg(feature = "mpi")]
+    #[error("Coordinated execution requires at least two MPI ranks")]
+    NoWorkers,
 }
 
 const ATOMIC_ORDERING: Ordering = Ordering::SeqCst;
 
+/// Parses every instance once and stores its features
+///
+/// Instances are shared between test sets, solvers and iterations, the local executor registers
+/// them from its parallel tasks where concurrent registrations wait for the first one to finish.
+#[derive(Debug, Default)]
+pub struct InstanceRegistry {
+    instances: Mutex<BTreeMap<PathBuf, Arc<OnceLock<Option<CowStr>>>>>,
+}
+
+impl InstanceRegistry {
+    /// hash of the instance at `path`, the instance is parsed and stored on its first registration
+    ///
+    /// Instances that couldn't be parsed or stored have no hash, their runs would otherwise
+    /// reference a missing instance.
+    pub fn register(&self, path: &Path, connection: &ConnectionAdapter) -> Option<CowStr> {
+        let instance = self
+            .instances
+            .lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .clone();
+
+        instance
+            .get_or_init(|| match dimacs::parse(path) {
+                Ok(features) => {
+                    debug!(path = ?path, hash = %features.hash, "Parsed instance {features:?}");
+
+                    match connection.store_instance(&features) {
+                        Ok(()) => Some(CowStr::from(features.hash)),
+                        Err(e) => {
+                            error!(error = ?e, path = ?path, "Failed to store instance: {e}");
+
+                            None
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(error = ?e, path = ?path, "Failed to parse instance: {e}");
+
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
 /// Executor that works on a local rayon-backed thread pool
 #[derive(Debug)]
 pub struct LocalExecutor<'a> {
@@ -43,6 +126,8 @@ pub struct LocalExecutor<'a> {
     connection: ConnectionAdapter,
     ingestors: IngestorMap<'a>,
     collectors: CollectorMap,
+    // runs of a resumed benchmark that are skipped
+    completed: CompletedRuns,
 }
 
 impl<'a> LocalExecutor<'a> {
@@ -52,12 +137,14 @@ impl<'a> LocalExecutor<'a> {
         config: SolverConfig,
         ingestors: IngestorMap<'a>,
         collectors: CollectorMap,
+        completed: CompletedRuns,
     ) -> Result<Self, ExecutorError> {
         Ok(Self {
             connection,
             config,
             ingestors,
             collectors,
+            completed,
         })
     }
 
@@ -122,6 +209,9 @@ impl<'a> LocalExecutor<'a> {
         let processed = AtomicU64::new(0);
         let total_iterations = AtomicU64::new(0);
         let errors = AtomicU64::new(0);
+        let wrong_answers = AtomicU64::new(0);
+        let instances = InstanceRegistry::default();
+        let connection = &self.connection;
 
         // find all files
         self.collectors
@@ -146,8 +236,7 @@ impl<'a> LocalExecutor<'a> {
                     ATOMIC_ORDERING,
                 );
 
-                // create actual tasks for all sets x solvers, including test metadata for
-                // ingesting
+                // create actual tasks for all sets x solvers, including test metadata for ingesting
                 iproduct!(
                     paths,
                     set.solvers
@@ -156,6 +245,8 @@ impl<'a> LocalExecutor<'a> {
                 )
                 .map(move |(path, solver)| (name.clone(), set.clone(), solver, path))
             })
+            // stop collecting new files once a shutdown was requested
+            .take_while(|_| !shutdown::requested())
             .par_bridge()
             .for_each(|(name, set, solver_name, file)| {
                 let span = span!(
@@ -167,17 +258,48 @@ impl<'a> LocalExecutor<'a> {
                 );
                 let _enter = span.enter();
 
+                if shutdown::requested() {
+                    file.interrupt();
+
+                    return;
+                }
+
                 debug!(
                     "Processing {:?} with {solver_name:?} for {name} with timeout {}",
                     file, set.timeout
                 );
 
+                // instances are hashed and stored by the first task of any solver reaching them
+                let instance = instances.register(&file, connection);
+
                 // TODO: Another map type may be used here to allow for fast access
                 // For testing this is sufficient though
                 let solver = self.config.solvers.get(&solver_name).unwrap_or_log();
-                let timeout = Duration::from_millis(set.timeout as u64);
+                let ingestor = self.ingestors.get(&solver.ingest).unwrap_or_log();
+
+                let completed = self
+                    .completed
+                    .get(&(
+                        name.clone(),
+                        solver_name.clone(),
+                        instance_key(instance.as_ref(), &file),
+                    ))
+                    .copied()
+                    .unwrap_or_default();
+
+                if completed >= set.iterations {
+                    debug!("Skipping, all {completed} iterations were completed already");
+                    processed.fetch_add(1, ATOMIC_ORDERING);
+
+                    return;
+                } else if completed > 0 {
+                    info!(
+                        "Resuming with iterations {completed}..{} of {name}/{solver_name}",
+                        set.iterations
+                    );
+                }
 
-                for iteration in 0..set.iterations {
+                for iteration in completed..set.iterations {
                     let span = span!(
                         Level::INFO,
                         "threadpool-execution-iteration",
@@ -185,106 +307,58 @@ impl<'a> LocalExecutor<'a> {
                     );
                     let _enter = span.enter();
 
-                    let start = Instant::now();
+                    if shutdown::requested() {
+                        file.interrupt();
+
+                        return;
+                    }
 
                     // this thread is created after the initial thread and inherits it's CPU affinity
-                    match Command::new(&solver.exec)
-                        .args(solver.params.iter().map(|solver| solver.as_str()))
-                        .args(set.params.iter().map(|solver| solver.as_str()))
-                        .arg(file.as_os_str())
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .spawn()
-                    {
-                        Ok(mut child) => match child.wait_timeout(timeout).unwrap_or_log() {
-                            Some(status) => {
-                                let mut output = RunOutput::new();
-                                output.status = status.code().unwrap_or(i32::MIN);
-
-                                // TODO: Add a lot of error fallback around this, in particular the
-                                // unwrap and expect parts that involve io
-                                output.runtime = start.elapsed().as_millis();
-                                let mut stdout = child.stdout.take().unwrap_or_log();
-                                stdout
-                                    .read_to_string(&mut output.stdout)
-                                    .expect("Failed to read stdout");
-
-                                let mut stderr = child.stderr.take().unwrap_or_log();
-                                stderr
-                                    .read_to_string(&mut output.stderr)
-                                    .expect("Failed to read stderr");
-
-                                debug!(
-                                    test = %name,
-                                    solver = %solver_name,
-                                    "Finished in {} ms | status: {}",
-                                    output.runtime,
-                                    status
-                                );
-
-                                match self.ingestors.get(&solver.ingest).unwrap().ingest(output) {
-                                    Ok(metrics) => {
-                                        debug!("Inserting {metrics:?}...");
-
-                                        match self.connection.store(
-                                            metrics,
-                                            solver_name.clone(),
-                                            name.clone(),
-                                            &file.to_path_buf(),
-                                        ) {
-                                            Ok(id) => {
-                                                debug!("Saved run {}", id);
-                                            }
-                                            Err(e) => {
-                                                error!(error = ?e, "Failed to insert metric: {e}");
-                                                errors.fetch_add(1, ATOMIC_ORDERING);
-                                            }
-                                        };
-                                    }
-                                    Err(e) => {
-                                        error!(
-                                            solver = %solver_name,
-                                            set = %name,
-                                            file = %file.to_string_lossy(),
-                                            error = ?e,
-                                            "Failed to ingest record: {e}"
-                                        );
-
-                                        errors.fetch_add(1, ATOMIC_ORDERING);
-                                    }
-                                };
+                    match run_solver(solver, &set, &file, ingestor) {
+                        Ok(metrics) => {
+                            debug!("Inserting {metrics:?}...");
+
+                            if matches!(
+                                metrics.status,
+                                RunStatus::SpawnError | RunStatus::IngestError
+                            ) {
+                                errors.fetch_add(1, ATOMIC_ORDERING);
                             }
-                            None => {
-                                // child hasn't exited yet
-                                child.kill().unwrap_or_log();
-
-                                debug!(
-                                    set = %name,
-                                    solver = %solver_name,
-                                    file = %file.to_string_lossy(),
-                                    "Killed due to timeout"
-                                );
-
-                                // NOTE: This is guaranteed by the timeout in the config being limited
-                                // in size
-                                match self.connection.store(
-                                    TestMetrics::failed(),
-                                    solver_name.clone(),
-                                    name.clone(),
-                                    &file.to_path_buf(),
-                                ) {
-                                    Ok(id) => {
-                                        debug!(id = id, "Saved run {id}");
-                                    }
-                                    Err(e) => {
-                                        error!(error = ?e, "Failed to insert metric: {e}");
-                                        errors.fetch_add(1, ATOMIC_ORDERING);
-                                    }
-                                };
+
+                            if metrics.verification == Verification::Invalid {
+                                wrong_answers.fetch_add(1, ATOMIC_ORDERING);
                             }
-                        },
+
+                            match self.connection.store(
+                                metrics,
+                                solver_name.clone(),
+                                name.clone(),
+                                &file.to_path_buf(),
+                                instance.clone(),
+                            ) {
+                                Ok(id) => {
+                                    debug!(id = id, "Saved run {id}");
+                                }
+                                Err(e) => {
+                                    error!(error = ?e, "Failed to insert metric: {e}");
+                                    errors.fetch_add(1, ATOMIC_ORDERING);
+                                }
+                            };
+                        }
+                        Err(ExecutorError::Interrupted) => {
+                            info!("Interrupted run, it is repeated when the benchmark is resumed");
+                            file.interrupt();
+
+                            return;
+                        }
                         Err(e) => {
-                            warn!(error = ?e, "Failed to spawn child process: {e}");
+                            error!(
+                                solver = %solver_name,
+                                set = %name,
+                                file = %file.to_string_lossy(),
+                                error = ?e,
+                                "Failed to execute run: {e}"
+                            );
 
                             errors.fetch_add(1, ATOMIC_ORDERING);
                         }
@@ -307,7 +381,13 @@ impl<'a> LocalExecutor<'a> {
                 processed.fetch_add(1, ATOMIC_ORDERING);
             });
 
-        self.connection.close()?;
+        let interrupted = shutdown::requested();
+
+        self.connection.finish(interrupted)?;
+
+        if interrupted {
+            warn!("Execution was interrupted, continue it with --resume");
+        }
 
         // finish the whole thing with a small confirmation message
         info!(
@@ -322,6 +402,391 @@ impl<'a> LocalExecutor<'a> {
             warn!("{encountered_errors} errors were encountered during execution, consult the logs for more information")
         }
 
+        let wrong_answers = wrong_answers.load(ATOMIC_ORDERING);
+
+        if wrong_answers > 0 {
+            error!("{wrong_answers} runs produced WRONG ANSWERS, they are stored with verification = -1")
+        }
+
         Ok(())
     }
 }
+
+/// Reap `pid` with wait4(2) and return its exit status together with its resource usage
+fn wait4(pid: pid_t) -> io::Result<(ExitStatus, ResourceUsage)> {
+    let mut status = 0;
+    let mut usage = MaybeUninit::<rusage>::zeroed();
+
+    loop {
+        // SAFETY: status and usage are valid for writes, wait4 fully initializes usage on success
+        let result = unsafe { libc::wait4(pid, &mut status, 0, usage.as_mut_ptr()) };
+
+        if result == pid {
+            break;
+        }
+
+        let error = io::Error::last_os_error();
+
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let usage = unsafe { usage.assume_init() };
+    let micros = |time: libc::timeval| time.tv_sec as u64 * 1_000_000 + time.tv_usec as u64;
+
+    Ok((
+        ExitStatus::from_raw(status),
+        ResourceUsage {
+            // ru_maxrss is already in KiB on Linux
+            max_rss: usage.ru_maxrss as u64,
+            user_time: micros(usage.ru_utime),
+            system_time: micros(usage.ru_stime),
+            voluntary_context_switches: usage.ru_nvcsw as u64,
+            involuntary_context_switches: usage.ru_nivcsw as u64,
+            minor_page_faults: usage.ru_minflt as u64,
+            major_page_faults: usage.ru_majflt as u64,
+        },
+    ))
+}
+
+/// interval in which a process group is checked for remaining members
+const GROUP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static SUBREAPER: Once = Once::new();
+
+/// Become the subreaper of all descendants, orphaned solver processes are then reparented to this
+/// process instead of init and can be reaped by `kill_process_group`
+fn register_subreaper() {
+    SUBREAPER.call_once(|| {
+        // SAFETY: PR_SET_CHILD_SUBREAPER only sets a flag of the calling process
+        if unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1) } != 0 {
+            warn!(
+                error = ?io::Error::last_os_error(),
+                "Failed to register as subreaper, orphaned solver processes are reaped by init"
+            );
+        }
+    });
+}
+
+/// send `signal` to all members of the process group `pgid`, an empty group is not an error
+fn signal_group(pgid: Pid, signal: Signal) -> io::Result<()> {
+    match killpg(pgid, signal) {
+        Ok(()) | Err(Errno::ESRCH) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Kill all remaining members of the process group `pgid` and wait until all of them were reaped
+///
+/// Wrapper scripts may exit before the solver they started, so the group can outlive the direct
+/// child. This must only be called after the direct child was reaped.
+fn kill_process_group(pgid: Pid) -> io::Result<()> {
+    loop {
+        match killpg(pgid, Signal::SIGKILL) {
+            Ok(()) => (),
+            Err(Errno::ESRCH) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+
+        // members are either reparented to this process or reaped by init
+        match waitpid(Pid::from_raw(-pgid.as_raw()), Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::StillAlive) | Err(Errno::ECHILD) => thread::sleep(GROUP_POLL_INTERVAL),
+            Ok(_) | Err(Errno::EINTR) => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// wait for `reader` to reach EOF and warn about truncated output
+fn captured(reader: PipeReader, stream: &str, file: &Path) -> io::Result<Captured> {
+    let captured = reader.finish()?;
+
+    if captured.truncated > 0 {
+        warn!(
+            file = %file.to_string_lossy(),
+            "Discarded {} bytes of {stream}, consider raising the output_limit",
+            captured.truncated
+        );
+    }
+
+    Ok(captured)
+}
+
+fn waiter_vanished() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "waiter thread vanished")
+}
+
+/// interval in which running solvers check for an expired shutdown
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// outcome of waiting on the waiter thread of a solver
+enum Wait<T> {
+    Exited(T),
+    Timeout,
+    Interrupted,
+}
+
+/// wait for the result of the waiter thread until `timeout`, or until a shutdown expired
+fn wait_for<T>(receiver: &Receiver<T>, timeout: Duration) -> io::Result<Wait<T>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if shutdown::expired() {
+            return Ok(Wait::Interrupted);
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        match receiver.recv_timeout(remaining.min(SHUTDOWN_POLL_INTERVAL)) {
+            Ok(result) => return Ok(Wait::Exited(result)),
+            Err(RecvTimeoutError::Timeout) if remaining <= SHUTDOWN_POLL_INTERVAL => {
+                return Ok(Wait::Timeout)
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return Err(waiter_vanished()),
+        }
+    }
+}
+
+/// exit codes of successful runs, SAT solvers conventionally exit with 10 (SAT) and 20 (UNSAT)
+const SUCCESS_CODES: [i32; 3] = [0, 10, 20];
+
+/// messages of common allocators and runtimes when an allocation fails
+const ALLOCATION_FAILURES: [&str; 4] = [
+    "std::bad_alloc",
+    "out of memory",
+    "cannot allocate memory",
+    "memory allocation failed",
+];
+
+/// Determine whether a failed run was caused by the memory limit
+///
+/// Allocations beyond RLIMIT_AS fail with ENOMEM instead of killing the process, the solver
+/// reports the failed allocation before it exits or aborts. The resident set size isn't
+/// comparable to the limit, which also covers mapped but untouched memory.
+fn is_memout(status: &ExitStatus, output: &RunOutput) -> bool {
+    if status.success() {
+        return false;
+    }
+
+    [&output.stderr, &output.stdout].into_iter().any(|stream| {
+        let stream = stream.to_lowercase();
+
+        ALLOCATION_FAILURES
+            .iter()
+            .any(|message| stream.contains(message))
+    })
+}
+
+/// Execute a single iteration of `solver` on `file` and ingest the output
+///
+/// Failed runs, e.g., due to timeouts, crashes or failed ingests, are reported as empty metrics
+/// with a matching `RunStatus`. Errors are only returned if the solver couldn't be supervised or
+/// was terminated due to a shutdown. The resource usage is measured by the executor for all runs.
+pub fn run_solver(
+    solver: &Solver,
+    set: &TestSet,
+    file: &Path,
+    ingestor: &Ingestors,
+) -> Result<TestMetrics, ExecutorError> {
+    let timeout = Duration::from_millis(set.timeout as u64);
+    let grace_period = Duration::from_millis(set.grace_period as u64);
+    let memory_limit = set.memory_limit(solver);
+
+    // the proof path is either substituted into the solver params or appended after the test file
+    let proof = match solver
+        .verify
+        .as_ref()
+        .and_then(|verify| verify.proof.as_ref())
+    {
+        Some(_) => Some(ProofFile::new()?),
+        None => None,
+    };
+    let mut substituted = false;
+    let params = solver.params.iter().map(|param| match &proof {
+        Some(proof) if param.contains(PROOF_PLACEHOLDER) => {
+            substituted = true;
+
+            param.replace(PROOF_PLACEHOLDER, &proof.path().to_string_lossy())
+        }
+        _ => param.to_string(),
+    });
+
+    let mut command = Command::new(&solver.exec);
+    command
+        .args(params.collect::<Vec<_>>())
+        .args(set.params.iter().map(|solver| solver.as_str()))
+        .arg(file.as_os_str());
+
+    if let (Some(proof), false) = (&proof, substituted) {
+        command.arg(proof.path());
+    }
+
+    command
+        // solvers are started in their own process group to terminate wrapper scripts together
+        // with the solvers they spawned
+        .process_group(0)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if let Some(limit) = memory_limit {
+        // SAFETY: setrlimit(2) is async-signal-safe and doesn't allocate
+        unsafe {
+            command.pre_exec(move || {
+                setrlimit(Resource::RLIMIT_AS, limit, limit).map_err(io::Error::from)
+            });
+        }
+    }
+
+    register_subreaper();
+
+    let start = Instant::now();
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            warn!(error = ?e, file = %file.to_string_lossy(), "Failed to spawn child process: {e}");
+
+            return Ok(TestMetrics::with_status(RunStatus::SpawnError));
+        }
+    };
+
+    // the child is reaped on a separate thread to retrieve its rusage while enforcing the timeout
+    let pid = child.id() as pid_t;
+    let pgid = Pid::from_raw(pid);
+
+    // both pipes are drained while waiting, solvers with a lot of output would block otherwise
+    let output_limit = solver.output_limit * 1024 * 1024;
+    let stdout = PipeReader::spawn(child.stdout.take().unwrap_or_log(), output_limit);
+    let stderr = PipeReader::spawn(child.stderr.take().unwrap_or_log(), output_limit);
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = wait4(pid).map(|(status, usage)| (status, usage, start.elapsed()));
+
+        // the receiver is only dropped after the result was received
+        let _ = sender.send(result);
+    });
+
+    match wait_for(&receiver, timeout)? {
+        Wait::Exited(result) => {
+            let (status, resources, runtime) = result?;
+
+            // descendants that are still running would otherwise keep the pipes open
+            kill_process_group(pgid)?;
+
+            let mut output = RunOutput::new();
+            output.status = status.code().unwrap_or(i32::MIN);
+            output.runtime = runtime.as_millis();
+
+            let Captured { content, truncated } = captured(stdout, "stdout", file)?;
+            output.stdout = content;
+            output.stderr = captured(stderr, "stderr", file)?.content;
+
+            debug!(
+                file = %file.to_string_lossy(),
+                "Finished in {} ms | status: {} | max rss: {} KiB",
+                output.runtime,
+                status,
+                resources.max_rss
+            );
+
+            if memory_limit.is_some() && is_memout(&status, &output) {
+                debug!(file = %file.to_string_lossy(), "Exceeded memory limit");
+
+                let mut metrics = TestMetrics::with_status(RunStatus::Memout);
+                metrics.resources = resources;
+
+                return Ok(metrics);
+            }
+
+            // output of crashed solvers isn't ingested, it is most likely incomplete
+            if let Some(signal) = status.signal() {
+                debug!(file = %file.to_string_lossy(), "Terminated by signal {signal}");
+
+                let mut metrics = TestMetrics::with_status(RunStatus::Signal(signal));
+                metrics.resources = resources;
+
+                return Ok(metrics);
+            }
+
+            let exit_status = match status.code() {
+                Some(code) if !SUCCESS_CODES.contains(&code) => RunStatus::ExitCode(code),
+                _ => RunStatus::Ok,
+            };
+
+            // the model has to be extracted before the output is handed to the ingestor
+            let model = solver
+                .verify
+                .as_ref()
+                .filter(|verify| verify.model)
+                .and_then(|_| verify::parse_model(&output.stdout));
+
+            let mut metrics = match ingestor.ingest(output) {
+                Ok(mut metrics) => {
+                    metrics.status = exit_status;
+
+                    if let (Some(config), RunStatus::Ok) = (&solver.verify, &metrics.status) {
+                        metrics.verification = verify::verify(
+                            config,
+                            &metrics.satisfiable,
+                            file,
+                            model.as_deref(),
+                            truncated > 0,
+                            proof.as_ref(),
+                        );
+                    }
+
+                    metrics
+                }
+                Err(e) => {
+                    warn!(error = ?e, file = %file.to_string_lossy(), "Failed to ingest record: {e}");
+
+                    // a failed ingest is only relevant if the solver itself succeeded
+                    TestMetrics::with_status(match exit_status {
+                        RunStatus::Ok => RunStatus::IngestError,
+                        status => status,
+                    })
+                }
+            };
+            metrics.resources = resources;
+
+            Ok(metrics)
+        }
+        waited @ (Wait::Timeout | Wait::Interrupted) => {
+            // child hasn't exited yet, give the solver a chance to terminate gracefully first
+            signal_group(pgid, Signal::SIGTERM)?;
+
+            let result = match receiver.recv_timeout(grace_period) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => {
+                    debug!(file = %file.to_string_lossy(), "Ignored SIGTERM, sending SIGKILL");
+
+                    signal_group(pgid, Signal::SIGKILL)?;
+                    receiver.recv().map_err(|_| waiter_vanished())?
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(waiter_vanished().into()),
+            };
+            let (_, resources, _) = result?;
+
+            kill_process_group(pgid)?;
+
+            // output of timed out runs is discarded, the readers are only joined to not leak them
+            stdout.finish()?;
+            stderr.finish()?;
+
+            if let Wait::Interrupted = waited {
+                debug!(file = %file.to_string_lossy(), "Killed due to shutdown");
+
+                return Err(ExecutorError::Interrupted);
+            }
+
+            debug!(file = %file.to_string_lossy(), "Killed due to timeout");
+
+            let mut metrics = TestMetrics::with_status(RunStatus::Timeout);
+            metrics.resources = resources;
+
+            Ok(metrics)
+        }
+    }
+}
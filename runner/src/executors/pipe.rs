@@ -0,0 +1,46 @@
+use std::{
+    io::{self, Read},
+    thread::{self, JoinHandle},
+};
+
+/// Content read from a pipe, output beyond the capture limit is drained but discarded
+#[derive(Debug, Default)]
+pub struct Captured {
+    pub content: String,
+    // number of bytes that were discarded
+    pub truncated: u64,
+}
+
+/// Drains a pipe on a background thread, this prevents children from blocking on full pipes
+#[derive(Debug)]
+pub struct PipeReader {
+    handle: JoinHandle<io::Result<Captured>>,
+}
+
+impl PipeReader {
+    /// start reading `pipe` until EOF, at most `limit` bytes are kept
+    pub fn spawn<R: Read + Send + 'static>(mut pipe: R, limit: u64) -> Self {
+        let handle = thread::spawn(move || {
+            let mut buffer = Vec::new();
+            (&mut pipe).take(limit).read_to_end(&mut buffer)?;
+
+            // keep reading, otherwise the writer blocks as soon as the pipe is full
+            let truncated = io::copy(&mut pipe, &mut io::sink())?;
+
+            Ok(Captured {
+                // the limit may split a multi-byte character
+                content: String::from_utf8_lossy(&buffer).into_owned(),
+                truncated,
+            })
+        });
+
+        Self { handle }
+    }
+
+    /// wait for EOF and return the captured content
+    pub fn finish(self) -> io::Result<Captured> {
+        self.handle
+            .join()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "pipe reader panicked"))?
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    collector::{
+        dimacs::{ClauseReader, DimacsError},
+        get_tmp_dir,
+    },
+    config::{ProofConfig, VerifyConfig},
+    database::{Satisfiability, Verification},
+    executors::pipe::PipeReader,
+};
+use std::{
+    fs::{create_dir_all, remove_file},
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use tracing::{debug, error, warn};
+use wait_timeout::ChildExt;
+
+/// placeholder in solver params that is replaced with the proof path
+pub const PROOF_PLACEHOLDER: &str = "{proof}";
+
+/// captured output of proof checkers, only the status line is of interest
+const CHECKER_OUTPUT_LIMIT: u64 = 1024 * 1024;
+
+static PROOF_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Proof written by a solver, the file is removed once it is dropped
+#[derive(Debug)]
+pub struct ProofFile {
+    path: PathBuf,
+}
+
+impl ProofFile {
+    /// reserve a unique proof path in the temporary directory
+    pub fn new() -> io::Result<Self> {
+        let dir = get_tmp_dir().join("satan-proofs");
+        create_dir_all(&dir)?;
+
+        let id = PROOF_COUNTER.fetch_add(1, Ordering::SeqCst);
+
+        Ok(Self {
+            path: dir.join(format!("{}-{id}.proof", std::process::id())),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ProofFile {
+    fn drop(&mut self) {
+        // solvers only write a proof for some answers
+        if let Err(e) = remove_file(&self.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                warn!(path = ?self.path, error = ?e, "Failed to remove proof: {e}");
+            }
+        }
+    }
+}
+
+/// parse the literals of all `v ...` lines, `None` is returned if no model was printed
+pub fn parse_model(stdout: &str) -> Option<Vec<i32>> {
+    let mut model = None;
+
+    for line in stdout.lines() {
+        if let Some(values) = line.strip_prefix("v ") {
+            model.get_or_insert_with(Vec::new).extend(
+                values
+                    .split_whitespace()
+                    .filter_map(|literal| literal.parse::<i32>().ok())
+                    .filter(|literal| *literal != 0),
+            );
+        }
+    }
+
+    model
+}
+
+/// Check whether `model` satisfies all clauses of the (compressed) DIMACS file `file`
+///
+/// Variables missing from the model are treated as unassigned and don't satisfy any literal.
+pub fn check_model(file: &Path, model: &[i32]) -> Result<bool, DimacsError> {
+    let variables = model.iter().map(|literal| literal.unsigned_abs()).max();
+    let mut assignment = vec![None; variables.unwrap_or_default() as usize + 1];
+
+    for literal in model {
+        assignment[literal.unsigned_abs() as usize] = Some(*literal > 0);
+    }
+
+    for clause in ClauseReader::open(file)? {
+        let satisfied = clause?.iter().any(|literal| {
+            assignment
+                .get(literal.unsigned_abs() as usize)
+                .copied()
+                .flatten()
+                == Some(*literal > 0)
+        });
+
+        if !satisfied {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// run the proof checker on `proof`, status lines take precedence over the exit code
+///
+/// Only an explicit `s NOT VERIFIED` rejects a proof, any other failure of the checker, e.g., an
+/// unsupported proof format or a crash, is an error of the verification.
+fn check_proof(config: &ProofConfig, file: &Path, proof: &Path) -> io::Result<Verification> {
+    if !proof.is_file() {
+        warn!(file = %file.to_string_lossy(), "Solver didn't write a proof");
+
+        return Ok(Verification::Error);
+    }
+
+    let mut child = Command::new(&config.checker)
+        .args(config.params.iter().map(|param| param.as_str()))
+        .arg(file)
+        .arg(proof)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let stdout = PipeReader::spawn(child.stdout.take().unwrap(), CHECKER_OUTPUT_LIMIT);
+
+    let status = match child.wait_timeout(Duration::from_millis(config.timeout as u64))? {
+        Some(status) => status,
+        None => {
+            warn!(file = %file.to_string_lossy(), "Proof checker ran into timeout");
+
+            child.kill()?;
+            child.wait()?;
+
+            return Ok(Verification::Error);
+        }
+    };
+
+    let stdout = stdout.finish()?.content;
+
+    for line in stdout.lines().map(str::trim) {
+        match line {
+            "s VERIFIED" => return Ok(Verification::Verified),
+            "s NOT VERIFIED" => return Ok(Verification::Invalid),
+            _ => (),
+        }
+    }
+
+    Ok(match status.code() {
+        Some(0) => Verification::Verified,
+        code => {
+            warn!(file = %file.to_string_lossy(), code = ?code, "Proof checker failed without a status line");
+
+            Verification::Error
+        }
+    })
+}
+
+/// Verify the answer of a run, answers without an enabled check stay unchecked
+///
+/// The model is incomplete if stdout was `truncated`, which can't be verified.
+pub fn verify(
+    config: &VerifyConfig,
+    satisfiable: &Satisfiability,
+    file: &Path,
+    model: Option<&[i32]>,
+    truncated: bool,
+    proof: Option<&ProofFile>,
+) -> Verification {
+    let result = match (satisfiable, model, &config.proof, proof) {
+        (Satisfiability::Satisfiable, ..) if config.model && truncated => {
+            warn!(file = %file.to_string_lossy(), "Model of a SAT answer was truncated");
+
+            Ok(Verification::Error)
+        }
+        (Satisfiability::Satisfiable, Some(model), ..) if config.model => {
+            match check_model(file, model) {
+                Ok(true) => Ok(Verification::Verified),
+                Ok(false) => Ok(Verification::Invalid),
+                Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            }
+        }
+        (Satisfiability::Satisfiable, None, ..) if config.model => {
+            warn!(file = %file.to_string_lossy(), "SAT answer without a model");
+
+            Ok(Verification::Error)
+        }
+        (Satisfiability::Unsatisfiable, _, Some(checker), Some(proof)) => {
+            check_proof(checker, file, proof.path())
+        }
+        _ => Ok(Verification::Unchecked),
+    };
+
+    match result {
+        Ok(Verification::Invalid) => {
+            error!(
+                file = %file.to_string_lossy(),
+                answer = ?satisfiable,
+                "WRONG ANSWER: verification of {satisfiable:?} failed"
+            );
+
+            Verification::Invalid
+        }
+        Ok(verification) => {
+            debug!(file = %file.to_string_lossy(), "Verification result: {verification:?}");
+
+            verification
+        }
+        Err(e) => {
+            warn!(file = %file.to_string_lossy(), error = ?e, "Failed to verify answer: {e}");
+
+            Verification::Error
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::verify::{check_model, parse_model, verify, ProofFile};
+use crate::{
+    config::{ProofConfig, VerifyConfig},
+    database::{Satisfiability, Verification},
+    test_util::TempPath,
+};
+use flate2::{write::GzEncoder, Compression};
+use std::{fs::write, io::Write};
+
+const CNF: &str = "c example\np cnf 3 3\n1 -2 0\n2 3 0\n-1\n-3 0\n";
+
+#[test]
+fn parse_model_lines() {
+    let stdout = "c comment\ns SATISFIABLE\nv 1 -2\nv 3 0\n";
+
+    assert_eq!(parse_model(stdout), Some(vec![1, -2, 3]));
+    assert_eq!(parse_model("s UNSATISFIABLE\n"), None);
+}
+
+#[test]
+fn check_model_clauses() {
+    let path = TempPath::with_content("plain.cnf", CNF.as_bytes());
+
+    assert!(check_model(&path, &[1, 2, -3]).unwrap());
+    assert!(!check_model(&path, &[1, -2, -3]).unwrap());
+    // clauses spanning multiple lines
+    assert!(!check_model(&path, &[1, 2, 3]).unwrap());
+    // unassigned variables don't satisfy literals
+    assert!(!check_model(&path, &[1, 2]).unwrap());
+}
+
+#[test]
+fn check_model_compressed() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(CNF.as_bytes()).unwrap();
+    let path = TempPath::with_content("compressed.cnf.gz", &encoder.finish().unwrap());
+
+    assert!(check_model(&path, &[1, 2, -3]).unwrap());
+}
+
+#[test]
+fn verify_truncated_model() {
+    let path = TempPath::with_content("truncated.cnf", CNF.as_bytes());
+    let config = VerifyConfig {
+        model: true,
+        proof: None,
+    };
+    let satisfiable = Satisfiability::Satisfiable;
+
+    // the output limit cut off the assignment of the last variable
+    let model = parse_model("s SATISFIABLE\nv 1 2").unwrap();
+
+    assert_eq!(
+        verify(&config, &satisfiable, &path, Some(&model), true, None),
+        Verification::Error
+    );
+    assert_eq!(
+        verify(&config, &satisfiable, &path, Some(&model), false, None),
+        Verification::Invalid
+    );
+}
+
+#[test]
+fn verify_proof_checker_failures() {
+    let path = TempPath::with_content("proof.cnf", CNF.as_bytes());
+    let proof = ProofFile::new().unwrap();
+    write(proof.path(), "0\n").unwrap();
+
+    let result = |script: &str| {
+        let config = VerifyConfig {
+            model: false,
+            proof: Some(ProofConfig {
+                checker: "/bin/sh".into(),
+                params: vec!["-c".into(), script.into(), "checker".into()],
+                timeout: 10000,
+            }),
+        };
+
+        verify(
+            &config,
+            &Satisfiability::Unsatisfiable,
+            &path,
+            None,
+            false,
+            Some(&proof),
+        )
+    };
+
+    assert_eq!(result("echo 's VERIFIED'"), Verification::Verified);
+    assert_eq!(
+        result("echo 's NOT VERIFIED'; exit 1"),
+        Verification::Invalid
+    );
+    // a checker failing without a verdict didn't reject the proof
+    assert_eq!(
+        result("echo 'invalid proof format'; exit 1"),
+        Verification::Error
+    );
+}
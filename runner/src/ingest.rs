@@ -1,13 +1,16 @@
 pub mod exec;
 
+#[cfg(test)]
+mod exec_test;
+
 use crate::{
     config::{ConfigErrors, IngestorConfig},
-    database::TestMetrics,
+    database::{MetricSchema, MetricType, TestMetrics},
 };
 use cowstr::CowStr;
-use std::{borrow::Cow, collections::BTreeMap, path::Path};
+use std::{borrow::Cow, collections::BTreeMap, mem, path::Path};
 use thiserror::Error;
-use tracing::error;
+use tracing::{debug, error};
 
 #[derive(Debug, Error)]
 pub enum IngestorError {
@@ -20,6 +23,8 @@ pub enum IngestorError {
     ChildError(#[from] std::io::Error),
     #[error("Ingestor timeout")]
     ChildTimeout,
+    #[error("Metric {0} isn't a valid {1:?}")]
+    InvalidMetric(CowStr, MetricType),
 }
 
 #[derive(Debug, Clone)]
@@ -72,9 +77,18 @@ impl Ingestors<'_> {
     #[tracing::instrument(level = "debug")]
     pub fn ingest(&self, output: RunOutput) -> Result<TestMetrics, IngestorError> {
         match self {
-            Self::Exec(ingestor) => ingestor.ingest(output),
+            Self::Exec(ingestor) => {
+                let mut metrics = ingestor.ingest(output)?;
+                check_metrics(&mut metrics, &ingestor.metrics)?;
+
+                Ok(metrics)
+            }
             Self::Null => match serde_yaml::from_str(&output.stdout) {
-                Ok(metrics) => Ok(metrics),
+                Ok(mut metrics) => {
+                    check_metrics(&mut metrics, &MetricSchema::new())?;
+
+                    Ok(metrics)
+                }
                 Err(error) => {
                     error!(error = ?error, "Failed to deserialize metrics for null ingestor");
 
@@ -84,3 +98,27 @@ impl Ingestors<'_> {
         }
     }
 }
+
+/// Check the solver specific metrics against the declared `schema`
+///
+/// Undeclared metrics are dropped as there is no column to store them in, metrics of the wrong
+/// type fail the whole run.
+fn check_metrics(metrics: &mut TestMetrics, schema: &MetricSchema) -> Result<(), IngestorError> {
+    for (name, value) in mem::take(&mut metrics.extra) {
+        match schema.get(&name) {
+            Some(metric_type) => match value.coerce(*metric_type) {
+                Some(value) => {
+                    metrics.extra.insert(name, value);
+                }
+                None => {
+                    error!(metric = %name, "Ingestor reported metric {name} that isn't a valid {metric_type:?}");
+
+                    return Err(IngestorError::InvalidMetric(name, *metric_type));
+                }
+            },
+            None => debug!(metric = %name, "Dropping undeclared metric {name}"),
+        }
+    }
+
+    Ok(())
+}
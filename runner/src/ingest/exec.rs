@@ -1,25 +1,31 @@
 use super::{IngestorError, RunOutput};
 use crate::{
     config::{ConfigErrors, IngestorConfig},
-    database::TestMetrics,
+    database::{MetricSchema, TestMetrics},
+    executors::pipe::PipeReader,
 };
 use std::{
     borrow::Cow,
     ffi::OsStr,
-    io::{Read, Write},
+    io::{self, ErrorKind, Write},
     process::{Command, Stdio},
+    thread,
     time::Duration,
 };
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, error, trace};
 use tracing_unwrap::OptionExt;
-use tracing_unwrap::ResultExt;
 use wait_timeout::ChildExt;
 
+/// maximum captured output of ingestors per stream, metrics are expected to be small
+const OUTPUT_LIMIT: u64 = 16 * 1024 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct ExecIngestor<'a> {
     pub ingestor: Cow<'a, OsStr>,
     pub params: Cow<'a, OsStr>,
     pub timeout: Duration,
+    // declared solver specific metrics, all others are dropped
+    pub metrics: MetricSchema,
 }
 
 impl<'a> ExecIngestor<'a> {
@@ -29,6 +35,7 @@ impl<'a> ExecIngestor<'a> {
                 ingestor: Cow::from(config.executable.clone().as_os_str().to_owned()),
                 timeout: Duration::from_millis(config.timeout),
                 params: Cow::from(OsStr::new(config.params.clone().as_str()).to_owned()),
+                metrics: config.metrics.clone(),
             }),
             _ => unreachable!(),
         }
@@ -36,7 +43,6 @@ impl<'a> ExecIngestor<'a> {
 
     #[tracing::instrument(level = "debug")]
     pub fn ingest(&self, output: RunOutput) -> Result<TestMetrics, super::IngestorError> {
-        // TODO: add configurable timeout for ingestors, otherwiese they might lock a connection
         match Command::new(&self.ingestor)
             .arg(&self.params)
             .stdout(Stdio::piped())
@@ -45,17 +51,20 @@ impl<'a> ExecIngestor<'a> {
             .spawn()
         {
             Ok(mut handle) => {
-                let mut stdin = handle.stdin.take().unwrap_or_log();
+                // stdin is written while the output is drained, ingestors that answer before
+                // consuming all of their input would block on a full pipe otherwise
+                let stdout = PipeReader::spawn(handle.stdout.take().unwrap_or_log(), OUTPUT_LIMIT);
+                let stderr = PipeReader::spawn(handle.stderr.take().unwrap_or_log(), OUTPUT_LIMIT);
 
-                stdin
-                    .write_all(output.stdout.as_bytes())
-                    .expect("Failed to write to stdin pipe of ingestor");
-                // Dropping stdin here will close the underlying file descriptor
-                // this makes writing ingestors easier as they have a clear end of input for stind
-                drop(stdin);
+                let mut stdin = handle.stdin.take().unwrap_or_log();
+                let writer = thread::spawn(move || {
+                    // Dropping stdin afterwards will close the underlying file descriptor
+                    // this makes writing ingestors easier as they have a clear end of input for stind
+                    stdin.write_all(output.stdout.as_bytes())
+                });
 
                 debug!("Ingestor waiting on {}", handle.id());
-                let status = match handle.wait_timeout(self.timeout).unwrap_or_log() {
+                let status = match handle.wait_timeout(self.timeout)? {
                     Some(status) => {
                         debug!("Ingestor exit status: {status:?}");
 
@@ -64,17 +73,32 @@ impl<'a> ExecIngestor<'a> {
                     None => {
                         debug!("Ingestor ran into timeout, attempting to continue");
 
+                        // the pipes are closed after the ingestor exited, which ends all helpers
+                        handle.kill()?;
+                        handle.wait()?;
+
                         return Err(super::IngestorError::ChildTimeout);
                     }
                 };
 
-                if !status {
-                    let mut stderr_buffer = String::new();
-
-                    if let Some(mut stderr) = handle.stderr.take() {
-                        stderr.read_to_string(&mut stderr_buffer).unwrap();
+                match writer.join() {
+                    Ok(Ok(())) => (),
+                    // ingestors may exit without consuming their whole input
+                    Ok(Err(e)) if e.kind() == ErrorKind::BrokenPipe => {
+                        debug!("Ingestor closed stdin before reading all input")
+                    }
+                    Ok(Err(e)) => return Err(e.into()),
+                    Err(_) => {
+                        return Err(IngestorError::ChildError(io::Error::new(
+                            ErrorKind::Other,
+                            "stdin writer panicked",
+                        )))
                     }
+                }
 
+                let stderr_buffer = stderr.finish()?.content;
+
+                if !status {
                     debug!(
                         stderr = stderr_buffer,
                         message = "Ingestor failed to ingest input, attempting to continue"
@@ -82,14 +106,7 @@ impl<'a> ExecIngestor<'a> {
                 }
 
                 // retrieve output from ingestor
-                let mut output = handle
-                    .stdout
-                    .take()
-                    .expect("Failed to acquire stdout of ingestor handle");
-                let mut buffer = String::new();
-                output
-                    .read_to_string(&mut buffer)
-                    .expect("Failed to read output");
+                let buffer = stdout.finish()?.content;
 
                 trace!("Output from ingestor: {buffer}");
 
@@ -0,0 +1,72 @@
+use super::{exec::ExecIngestor, IngestorError, Ingestors, RunOutput};
+use crate::database::{MetricSchema, MetricType, MetricValue, Satisfiability};
+use std::{borrow::Cow, ffi::OsStr, time::Duration};
+
+const METRICS: &str = "runtime: 3
+satisfiable: 1
+parse_time: 1
+memory_usage: 2
+restarts: 0
+conflicts: 5
+propagations: 9
+conflict_literals: 4
+number_of_variables: 3
+number_of_clauses: 2
+";
+
+// `cat -` echoes the output of the solver back as metrics
+fn echo_ingestor(metrics: &[(&str, MetricType)]) -> Ingestors<'static> {
+    Ingestors::Exec(ExecIngestor {
+        ingestor: Cow::from(OsStr::new("cat")),
+        params: Cow::from(OsStr::new("-")),
+        timeout: Duration::from_secs(5),
+        metrics: metrics
+            .iter()
+            .map(|(name, metric_type)| ((*name).into(), *metric_type))
+            .collect::<MetricSchema>(),
+    })
+}
+
+fn output(extra: &str) -> RunOutput {
+    RunOutput {
+        stdout: format!("{METRICS}{extra}"),
+        ..RunOutput::new()
+    }
+}
+
+#[test]
+fn declared_metrics_are_kept() {
+    let ingestor = echo_ingestor(&[
+        ("decisions", MetricType::Int),
+        ("inprocessing_time", MetricType::Float),
+        ("phase", MetricType::String),
+    ]);
+
+    let metrics = ingestor
+        .ingest(output(
+            "decisions: 42\ninprocessing_time: 2\nphase: stable\nchronological: 7\n",
+        ))
+        .unwrap();
+
+    assert_eq!(metrics.satisfiable, Satisfiability::Satisfiable);
+    assert_eq!(metrics.conflicts, 5);
+    // integers are accepted as floats, undeclared metrics are dropped
+    assert_eq!(
+        metrics.extra.into_iter().collect::<Vec<_>>(),
+        vec![
+            ("decisions".into(), MetricValue::Int(42)),
+            ("inprocessing_time".into(), MetricValue::Float(2.0)),
+            ("phase".into(), MetricValue::String("stable".to_owned())),
+        ]
+    );
+}
+
+#[test]
+fn mistyped_metrics_are_rejected() {
+    let ingestor = echo_ingestor(&[("decisions", MetricType::Int)]);
+
+    assert!(matches!(
+        ingestor.ingest(output("decisions: many\n")),
+        Err(IngestorError::InvalidMetric(_, MetricType::Int))
+    ));
+}
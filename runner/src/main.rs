@@ -1,8 +1,12 @@
+mod analysis;
 mod collector;
 mod config;
 mod database;
 mod executors;
 mod ingest;
+mod shutdown;
+#[cfg(test)]
+mod test_util;
 
 #[cfg(feature = "distributed")]
 mod distributed;
@@ -26,8 +30,9 @@ use std::{
     os::unix::prelude::OsStrExt,
     path::PathBuf,
     process::exit,
+    time::Duration,
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 #[derive(Parser, Debug, Clone)]
@@ -67,6 +72,20 @@ enum Commands {
     Clean,
     /// Execute a benchmark suite
     Execute(ExecuteArgs),
+    /// Store the runs of a spool that was left behind by a crashed delayed connection
+    Recover(RecoverArgs),
+    /// Upgrade the schema of the configured database to the version of the runner
+    Migrate,
+    /// Export the runs of a benchmark with their solver, test set and benchmark attributes
+    Export(ExportArgs),
+    /// Copy all benchmarks, solvers, test sets, instances and runs between two databases
+    MigrateDb(MigrateDbArgs),
+    /// Summarize the runs of a benchmark per solver and test set
+    Report(ReportArgs),
+    /// Compare the runs of two benchmarks by solver and instance, exits with 2 on regressions
+    Compare(CompareArgs),
+    /// Record instances with conflicting answers of a benchmark, exits with 2 on conflicts
+    Check(CheckArgs),
 }
 
 #[derive(Clone, Debug, Args)]
@@ -91,11 +110,200 @@ pub struct ExecuteArgs {
         help = "test set that should be used in benchmark (default: all)"
     )]
     tests: Option<Vec<String>>,
+    #[arg(
+        short = 'r',
+        long = "resume",
+        help = "skip runs of the benchmark given with --benchmark that were completed already"
+    )]
+    resume: bool,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct RecoverArgs {
+    #[arg(value_hint = clap::ValueHint::FilePath, help = "spool to store in the database of the config")]
+    spool: PathBuf,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ExportArgs {
+    #[arg(
+        short = 'b',
+        long = "benchmark",
+        value_name = "BENCHMARK",
+        help = "benchmark to export"
+    )]
+    benchmark: i32,
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        default_value = "csv",
+        help = "format of the export"
+    )]
+    format: database::export::ExportFormat,
+    #[arg(
+        short = 's',
+        long = "solver",
+        value_name = "SOLVER",
+        help = "only export runs of this solver (default: all)"
+    )]
+    solvers: Vec<String>,
+    #[arg(
+        short = 't',
+        long = "test",
+        value_name = "TEST",
+        help = "only export runs of this test set (default: all)"
+    )]
+    tests: Vec<String>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_hint = clap::ValueHint::FilePath,
+        help = "file to write the export to (default: benchmark-<BENCHMARK>.<FORMAT>)"
+    )]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ReportArgs {
+    #[arg(
+        short = 'b',
+        long = "benchmark",
+        value_name = "BENCHMARK",
+        help = "benchmark to report"
+    )]
+    benchmark: i32,
+    #[arg(
+        short = 's',
+        long = "solver",
+        value_name = "SOLVER",
+        help = "only report runs of this solver (default: all)"
+    )]
+    solvers: Vec<String>,
+    #[arg(
+        short = 't',
+        long = "test",
+        value_name = "TEST",
+        help = "only report runs of this test set (default: all)"
+    )]
+    tests: Vec<String>,
+    #[arg(
+        long = "cactus",
+        value_hint = clap::ValueHint::FilePath,
+        help = "write the runtimes of all solved runs for cactus and CDF plots as CSV to this file"
+    )]
+    cactus: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct CheckArgs {
+    #[arg(
+        short = 'b',
+        long = "benchmark",
+        value_name = "BENCHMARK",
+        help = "benchmark to check"
+    )]
+    benchmark: i32,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct CompareArgs {
+    #[arg(
+        long = "baseline",
+        value_name = "BENCHMARK",
+        help = "benchmark to compare against"
+    )]
+    baseline: i32,
+    #[arg(
+        long = "candidate",
+        value_name = "BENCHMARK",
+        help = "benchmark to compare"
+    )]
+    candidate: i32,
+    #[arg(
+        short = 's',
+        long = "solver",
+        value_name = "SOLVER",
+        help = "only compare runs of this solver (default: all)"
+    )]
+    solvers: Vec<String>,
+    #[arg(
+        short = 't',
+        long = "test",
+        value_name = "TEST",
+        help = "only compare runs of this test set (default: all)"
+    )]
+    tests: Vec<String>,
+    #[arg(
+        long = "threshold",
+        default_value_t = 0.05,
+        help = "relative PAR-2 slowdown of a solver that is a regression"
+    )]
+    threshold: f64,
+    #[arg(
+        long = "alpha",
+        default_value_t = 0.05,
+        help = "significance level a slowdown has to reach to be a regression"
+    )]
+    alpha: f64,
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_hint = clap::ValueHint::FilePath,
+        help = "write the comparison of every instance as CSV to this file"
+    )]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MigrateDbArgs {
+    #[arg(
+        long = "from",
+        value_parser = parse_connection,
+        help = "database to copy from, given as YAML (e.g., '!SQLite { path: satan.db }') or a YAML file"
+    )]
+    from: config::ConnectionConfig,
+    #[arg(
+        long = "to",
+        value_parser = parse_connection,
+        help = "database to copy to, given as YAML or a YAML file"
+    )]
+    to: config::ConnectionConfig,
+    #[arg(
+        long = "state",
+        value_hint = clap::ValueHint::FilePath,
+        default_value = "satan-migrate-db.json",
+        help = "progress of the migration, an interrupted migration is resumed with the same state"
+    )]
+    state: PathBuf,
+    #[arg(
+        long = "chunk-size",
+        default_value_t = 10000,
+        help = "number of runs that are copied at once"
+    )]
+    chunk_size: usize,
+}
+
+/// parse a connection config given as YAML or as the path to a YAML file
+fn parse_connection(value: &str) -> Result<config::ConnectionConfig, String> {
+    let path = PathBuf::from(value);
+
+    let yaml = if path.is_file() {
+        std::fs::read_to_string(&path).map_err(|error| error.to_string())?
+    } else {
+        value.to_owned()
+    };
+
+    serde_yaml::from_str(&yaml).map_err(|error| error.to_string())
 }
 
 #[derive(Clone, Debug, Args)]
 pub struct MergeArgs {
-    #[arg(short = 'd', long = "databases", help = "databases to merge")]
+    #[arg(
+        short = 'd',
+        long = "databases",
+        help = "databases to merge into the database of the config"
+    )]
     databases: Vec<PathBuf>,
 }
 
@@ -151,7 +359,561 @@ fn main() -> Result<(), ConfigErrors> {
     debug!("Args: {args:?}");
 
     match args.command {
-        Commands::Merge { .. } => todo!(),
+        Commands::Merge(sub_args) => {
+            // the configured database is the target all other databases are merged into
+            let config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            let mut connection =
+                match database::ConnectionAdapter::load_connection(&config.database) {
+                    Ok(connection) => connection,
+                    Err(error) => {
+                        error!(error = ?error, "Failed to load connection: {error}");
+
+                        exit(1)
+                    }
+                };
+
+            let mut total = database::MergeReport::default();
+
+            for path in sub_args.databases {
+                if !path.is_file() {
+                    error!(path = ?path, "{} is not a database file", path.to_string_lossy());
+
+                    exit(1);
+                }
+
+                let source = match config.database.connection.with_path(path.clone()) {
+                    Some(source) => source,
+                    None => {
+                        error!("Merging is only supported for SQLite and DuckDB databases");
+
+                        exit(1);
+                    }
+                };
+
+                match connection.merge(&source) {
+                    Ok(report) => {
+                        info!(
+                            path = ?path,
+                            "Merged {} benchmarks, {} solvers ({} reused), {} test sets ({} reused), {} runs, {} metric rows and {} instances",
+                            report.benchmarks,
+                            report.solvers_created,
+                            report.solvers_reused,
+                            report.test_sets_created,
+                            report.test_sets_reused,
+                            report.runs,
+                            report.metrics,
+                            report.instances
+                        );
+
+                        total.benchmarks += report.benchmarks;
+                        total.solvers_created += report.solvers_created;
+                        total.solvers_reused += report.solvers_reused;
+                        total.test_sets_created += report.test_sets_created;
+                        total.test_sets_reused += report.test_sets_reused;
+                        total.runs += report.runs;
+                        total.instances += report.instances;
+                        total.metrics += report.metrics;
+                    }
+                    Err(error @ database::ConnectionError::OutdatedSchema(_)) => {
+                        error!(
+                            path = ?path,
+                            "Failed to merge database: {error} on {}",
+                            path.to_string_lossy()
+                        );
+
+                        exit(1);
+                    }
+                    Err(error) => {
+                        error!(error = ?error, path = ?path, "Failed to merge database: {error}");
+
+                        exit(1);
+                    }
+                }
+            }
+
+            info!(
+                "Done with merging: {} benchmarks, {} solvers ({} reused), {} test sets ({} reused), {} runs, {} metric rows and {} instances",
+                total.benchmarks,
+                total.solvers_created,
+                total.solvers_reused,
+                total.test_sets_created,
+                total.test_sets_reused,
+                total.runs,
+                total.metrics,
+                total.instances
+            );
+
+            connection.close()?;
+
+            Ok(())
+        }
+        Commands::Recover(sub_args) => {
+            // solvers and test sets of the spooled runs are resolved with the config
+            let mut config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            // spooled runs reference the variants of swept solvers
+            if config.expand_sweeps() {
+                error!("Config contains one or more errors, see previous error messages");
+
+                exit(1);
+            }
+
+            // the spool belongs to the database of this host
+            config.host_specific_database();
+
+            let (benchmark, runs) = match database::spool::read(&sub_args.spool) {
+                Ok(spool) => spool,
+                Err(error) => {
+                    error!(error = ?error, path = ?sub_args.spool, "Failed to read spool: {error}");
+
+                    exit(1)
+                }
+            };
+
+            let mut connection =
+                match database::ConnectionAdapter::load_connection(&config.database) {
+                    Ok(connection) => connection,
+                    Err(error) => {
+                        error!(error = ?error, "Failed to load connection: {error}");
+
+                        exit(1)
+                    }
+                };
+
+            if let Err(error) = connection.init(&config, Some(benchmark), None) {
+                error!(error = ?error, "Failed to initialize the database connection: {error}");
+
+                exit(1)
+            };
+
+            let mut recovered = 0;
+
+            // run IDs are only reserved in memory and may have been taken by other runs since the
+            // crash, the recovered runs are assigned new IDs
+            let runs = runs.map(|mut bundle| {
+                recovered += 1;
+                bundle.id = None;
+
+                bundle
+            });
+
+            match connection.store_iter(runs) {
+                Ok(()) => (),
+                Err(database::ConnectionError::DanglingReference(reference)) => {
+                    error!(
+                        "Spooled runs reference {reference}, which is not part of the config anymore, restore it to recover the spool"
+                    );
+
+                    exit(1)
+                }
+                Err(error) => {
+                    error!(error = ?error, "Failed to store spooled runs: {error}");
+
+                    exit(1)
+                }
+            }
+
+            connection.close()?;
+
+            if let Err(error) = std::fs::remove_file(&sub_args.spool) {
+                warn!(error = ?error, "Failed to remove recovered spool: {error}");
+            }
+
+            info!("Recovered {recovered} runs of benchmark {benchmark}");
+
+            Ok(())
+        }
+        Commands::Migrate => {
+            let config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            let mut connection =
+                match database::ConnectionAdapter::load_connection(&config.database) {
+                    Ok(connection) => connection,
+                    Err(error) => {
+                        error!(error = ?error, "Failed to load connection: {error}");
+
+                        exit(1)
+                    }
+                };
+
+            match connection.migrate() {
+                Ok((from, to)) if from == to => {
+                    info!("Database schema is up to date (version {to})")
+                }
+                Ok((0, to)) => info!("Created database schema (version {to})"),
+                Ok((from, to)) => info!("Migrated database schema from version {from} to {to}"),
+                Err(database::ConnectionError::ConfigError) => {
+                    error!("Migrations are only supported for SQLite and DuckDB databases");
+
+                    exit(1)
+                }
+                Err(error) => {
+                    error!(error = ?error, "Failed to migrate database: {error}");
+
+                    exit(1)
+                }
+            }
+
+            connection.close()?;
+
+            Ok(())
+        }
+        Commands::Export(sub_args) => {
+            let config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            let connection = match database::ConnectionAdapter::load_connection(&config.database) {
+                Ok(connection) => connection,
+                Err(error) => {
+                    error!(error = ?error, "Failed to load connection: {error}");
+
+                    exit(1)
+                }
+            };
+
+            // reading an outdated schema would fail on missing columns
+            if let Err(error) = connection.check_schema() {
+                error!(error = ?error, "Failed to export runs: {error}");
+
+                exit(1)
+            }
+
+            let filter = database::export::ExportFilter {
+                benchmark: sub_args.benchmark,
+                solvers: sub_args.solvers,
+                test_sets: sub_args.tests,
+            };
+
+            let output = sub_args.output.unwrap_or_else(|| {
+                PathBuf::from(format!(
+                    "benchmark-{}.{}",
+                    filter.benchmark,
+                    sub_args.format.extension()
+                ))
+            });
+
+            // rows are written as they are read instead of collecting the whole benchmark
+            let mut exported = 0;
+            let result = database::export::ExportWriter::create(sub_args.format, &output).and_then(
+                |mut writer| {
+                    connection.export_each(&filter, |row| {
+                        exported += 1;
+
+                        writer.write(row)
+                    })?;
+
+                    writer.finish()
+                },
+            );
+
+            connection.close()?;
+
+            if let Err(error) = result {
+                if let Err(error) = std::fs::remove_file(&output) {
+                    debug!(error = ?error, path = ?output, "Failed to remove incomplete export");
+                }
+
+                match error {
+                    database::export::ExportError::Connection(
+                        database::ConnectionError::ConfigError,
+                    ) => error!("Exporting is only supported for SQLite and DuckDB databases"),
+                    error => {
+                        error!(error = ?error, path = ?output, "Failed to export runs: {error}")
+                    }
+                }
+
+                exit(1)
+            }
+
+            if exported == 0 {
+                warn!(
+                    "Found no runs of benchmark {} matching the filters",
+                    filter.benchmark
+                );
+            }
+
+            info!(path = ?output, "Exported {exported} runs to {}", output.to_string_lossy());
+
+            Ok(())
+        }
+        Commands::Report(sub_args) => {
+            let config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            let connection = match database::ConnectionAdapter::load_connection(&config.database) {
+                Ok(connection) => connection,
+                Err(error) => {
+                    error!(error = ?error, "Failed to load connection: {error}");
+
+                    exit(1)
+                }
+            };
+
+            if let Err(error) = connection.check_schema() {
+                error!(error = ?error, "Failed to collect runs: {error}");
+
+                exit(1)
+            }
+
+            let filter = database::export::ExportFilter {
+                benchmark: sub_args.benchmark,
+                solvers: sub_args.solvers,
+                test_sets: sub_args.tests,
+            };
+
+            let rows = match connection.export(&filter) {
+                Ok(rows) => rows,
+                Err(database::ConnectionError::ConfigError) => {
+                    error!("Reports are only supported for SQLite and DuckDB databases");
+
+                    exit(1)
+                }
+                Err(error) => {
+                    error!(error = ?error, "Failed to collect runs: {error}");
+
+                    exit(1)
+                }
+            };
+
+            connection.close()?;
+
+            if rows.is_empty() {
+                warn!(
+                    "Found no runs of benchmark {} matching the filters",
+                    filter.benchmark
+                );
+
+                return Ok(());
+            }
+
+            print!(
+                "{}",
+                analysis::report::table(&analysis::report::report(&rows))
+            );
+
+            let conflicts = analysis::check::check(&rows);
+            if !conflicts.is_empty() {
+                warn!(
+                    "Found conflicting answers on {} instances, see `satan-runner check --benchmark {}`",
+                    conflicts.len(),
+                    filter.benchmark
+                );
+            }
+
+            if let Some(path) = sub_args.cactus {
+                if let Err(error) = analysis::report::write_cactus(&rows, &path) {
+                    error!(error = ?error, path = ?path, "Failed to write cactus data: {error}");
+
+                    exit(1)
+                }
+
+                info!(path = ?path, "Wrote cactus data to {}", path.to_string_lossy());
+            }
+
+            Ok(())
+        }
+        Commands::Check(sub_args) => {
+            let config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            let connection = match database::ConnectionAdapter::load_connection(&config.database) {
+                Ok(connection) => connection,
+                Err(error) => {
+                    error!(error = ?error, "Failed to load connection: {error}");
+
+                    exit(1)
+                }
+            };
+
+            let conflicts = match analysis::check::check_benchmark(&connection, sub_args.benchmark)
+            {
+                Ok(conflicts) => conflicts,
+                Err(database::ConnectionError::ConfigError) => {
+                    error!("Checks are only supported for SQLite and DuckDB databases");
+
+                    exit(1)
+                }
+                Err(error @ database::ConnectionError::OutdatedSchema(_)) => {
+                    error!("Failed to check benchmark: {error}");
+
+                    exit(1)
+                }
+                Err(error) => {
+                    error!(error = ?error, "Failed to check benchmark: {error}");
+
+                    exit(1)
+                }
+            };
+
+            connection.close()?;
+
+            if conflicts.is_empty() {
+                info!(
+                    "Found no conflicting answers in benchmark {}",
+                    sub_args.benchmark
+                );
+
+                return Ok(());
+            }
+
+            print!("{}", analysis::check::table(&conflicts));
+            error!(
+                "Found conflicting answers on {} instances of benchmark {}",
+                conflicts.len(),
+                sub_args.benchmark
+            );
+
+            exit(2)
+        }
+        Commands::Compare(sub_args) => {
+            let config: config::SolverConfig = config::SolverConfig::load(&args.config);
+
+            let connection = match database::ConnectionAdapter::load_connection(&config.database) {
+                Ok(connection) => connection,
+                Err(error) => {
+                    error!(error = ?error, "Failed to load connection: {error}");
+
+                    exit(1)
+                }
+            };
+
+            if let Err(error) = connection.check_schema() {
+                error!(error = ?error, "Failed to collect runs: {error}");
+
+                exit(1)
+            }
+
+            let collect = |benchmark| {
+                let filter = database::export::ExportFilter {
+                    benchmark,
+                    solvers: sub_args.solvers.clone(),
+                    test_sets: sub_args.tests.clone(),
+                };
+
+                match connection.export(&filter) {
+                    Ok(rows) if rows.is_empty() => {
+                        error!("Found no runs of benchmark {benchmark} matching the filters");
+
+                        exit(1)
+                    }
+                    Ok(rows) => rows,
+                    Err(database::ConnectionError::ConfigError) => {
+                        error!("Comparisons are only supported for SQLite and DuckDB databases");
+
+                        exit(1)
+                    }
+                    Err(error) => {
+                        error!(error = ?error, "Failed to collect runs: {error}");
+
+                        exit(1)
+                    }
+                }
+            };
+
+            let (baseline, candidate) = (collect(sub_args.baseline), collect(sub_args.candidate));
+            connection.close()?;
+
+            let comparison = analysis::compare::compare(
+                &baseline,
+                &candidate,
+                sub_args.threshold,
+                sub_args.alpha,
+            );
+
+            print!(
+                "{}\n{}",
+                analysis::compare::instance_table(&comparison, sub_args.threshold),
+                analysis::compare::solver_table(&comparison)
+            );
+
+            if let Some(path) = sub_args.output {
+                if let Err(error) = analysis::compare::write_instances(&comparison, &path) {
+                    error!(error = ?error, path = ?path, "Failed to write comparison: {error}");
+
+                    exit(1)
+                }
+
+                info!(path = ?path, "Wrote comparison to {}", path.to_string_lossy());
+            }
+
+            let regressions = comparison
+                .solvers
+                .iter()
+                .filter(|solver| solver.regression)
+                .map(|solver| solver.solver.as_str())
+                .collect::<Vec<_>>();
+
+            if !regressions.is_empty() {
+                error!(
+                    "Benchmark {} regressed from {} for {}",
+                    sub_args.candidate,
+                    sub_args.baseline,
+                    regressions.join(", ")
+                );
+
+                exit(2)
+            }
+
+            Ok(())
+        }
+        Commands::MigrateDb(sub_args) => {
+            let load = |connection: config::ConnectionConfig| {
+                database::ConnectionAdapter::load_connection(&config::DatabaseConfig {
+                    delayed: false,
+                    spool: None,
+                    batched: None,
+                    connection,
+                })
+            };
+
+            let (source, mut target) = match (load(sub_args.from), load(sub_args.to)) {
+                (Ok(source), Ok(target)) => (source, target),
+                (Err(error), _) | (_, Err(error)) => {
+                    error!(error = ?error, "Failed to load connection: {error}");
+
+                    exit(1)
+                }
+            };
+
+            match database::transfer::transfer(
+                &source,
+                &mut target,
+                &sub_args.state,
+                sub_args.chunk_size.max(1),
+            ) {
+                Ok(report) => info!(
+                    "Migrated {} benchmarks, {} solvers ({} reused), {} test sets ({} reused), {} runs, {} metric rows and {} instances",
+                    report.benchmarks,
+                    report.solvers_created,
+                    report.solvers_reused,
+                    report.test_sets_created,
+                    report.test_sets_reused,
+                    report.runs,
+                    report.metrics,
+                    report.instances
+                ),
+                Err(error @ database::ConnectionError::OutdatedSchema(_)) => {
+                    error!("Failed to migrate database: {error} on the source first");
+
+                    exit(1)
+                }
+                Err(error) => {
+                    error!(
+                        error = ?error,
+                        "Failed to migrate database: {error}, run the same command again to resume"
+                    );
+
+                    exit(1)
+                }
+            }
+
+            source.close()?;
+            target.close()?;
+
+            if let Err(error) = std::fs::remove_file(&sub_args.state) {
+                warn!(error = ?error, "Failed to remove migration state: {error}");
+            }
+
+            Ok(())
+        }
         Commands::Clean => {
             // determine if the solver follows the correct syntax, exists ...
             let mut config: config::SolverConfig = config::SolverConfig::load(&args.config);
@@ -249,6 +1011,36 @@ fn main() -> Result<(), ConfigErrors> {
 
             debug!("Config: {config:?}");
 
+            if sub_args.resume && args.benchmark.is_none() {
+                error!("Resuming requires the benchmark to continue, e.g., --benchmark 1");
+
+                exit(1);
+            }
+
+            // interrupted benchmarks are flushed and marked in the database instead of being lost
+            let grace_period = Duration::from_millis(config.shutdown_grace_period as u64);
+
+            if let Err(error) = shutdown::install(grace_period) {
+                error!(error = ?error, "Failed to install signal handlers: {error}");
+
+                exit(1)
+            }
+
+            // workers of a coordinated run must not touch the database, it is dispatched before
+            // the connection is established
+            #[cfg(feature = "distributed")]
+            if let ExecutorConfig::Distributed {
+                synchronization: SynchronizationTypes::Coordinated,
+            } = config.executor
+            {
+                return execute_coordinated(
+                    config,
+                    args.benchmark,
+                    sub_args.comment,
+                    sub_args.resume,
+                );
+            }
+
             let mut connection = match database::ConnectionAdapter::load(&config.database) {
                 Ok(connection) => connection,
                 Err(error) => {
@@ -264,6 +1056,12 @@ fn main() -> Result<(), ConfigErrors> {
                 exit(1)
             };
 
+            let completed = if sub_args.resume {
+                completed_runs(&connection, &config).unwrap_or_else(|_| exit(1))
+            } else {
+                database::CompletedRuns::new()
+            };
+
             // TODO: Get rid of full clone here, this should be limited to config.ingest
             let cloned_config = config.clone();
             let ingestors = match cloned_config.load_ingestors() {
@@ -277,25 +1075,15 @@ fn main() -> Result<(), ConfigErrors> {
                 }
             };
 
-            // TODO: Inject MPICollector and FS Collector
             let collectors = match config.collectors() {
                 Ok(mut collectors) => match config.executor {
+                    #[cfg(feature = "distributed")]
                     ExecutorConfig::Distributed {
-                        ref synchronization,
+                        synchronization: SynchronizationTypes::FileSystem { .. },
                     } => {
-                        match synchronization {
-                            SynchronizationTypes::Coordinated => {
-                                // TODO: Create coordinator here and share across collectors
-                                collectors.iter_mut().for_each(|(_, value)| {
-                                    *value = Collector::mpi(value.clone());
-                                });
-                            }
-                            SynchronizationTypes::FileSystem { .. } => {
-                                collectors.iter_mut().for_each(|(_, value)| {
-                                    *value = Collector::fs(value.clone());
-                                });
-                            }
-                        };
+                        collectors.iter_mut().for_each(|(_, value)| {
+                            *value = Collector::fs(value.clone());
+                        });
 
                         collectors
                     }
@@ -307,34 +1095,218 @@ fn main() -> Result<(), ConfigErrors> {
                 }
             };
 
-            // select an executor ...
-            #[cfg(feature = "distributed")]
-            let executor = match config.executor {
-                ExecutorConfig::Distributed {
-                    synchronization: SynchronizationTypes::Coordinated,
-                } => todo!(),
-                ExecutorConfig::Distributed {
-                    synchronization: SynchronizationTypes::FileSystem { .. },
-                }
-                | ExecutorConfig::Local { .. } => {
-                    executors::LocalExecutor::load(connection, config, ingestors, collectors)
-                }
-            };
+            // the runs are checked for conflicting answers once all of them are stored
+            let (benchmark, database) = (connection.benchmark(), config.database.clone());
 
-            #[cfg(not(feature = "distributed"))]
-            let executor =
-                executors::LocalExecutor::load(connection, config, ingestors, collectors);
+            // select an executor ...
+            let executor = executors::LocalExecutor::load(
+                connection, config, ingestors, collectors, completed,
+            );
 
             // ... and throw the queue at it
             match executor {
                 Ok(executor) => match executor.execute() {
                     Ok(()) => info!("Finished execution"),
-                    Err(error) => error!(error = ?error, "Executor failed: {error}"),
+                    Err(error) => {
+                        error!(error = ?error, "Executor failed: {error}");
+
+                        return Ok(());
+                    }
                 },
-                Err(error) => error!(error = ?error, "Executor failed to initialize"),
+                Err(error) => {
+                    error!(error = ?error, "Executor failed to initialize");
+
+                    return Ok(());
+                }
             }
 
+            check_conflicts(&database, benchmark);
+
             Ok(())
         }
     }
 }
+
+/// Load the completed runs of a resumed benchmark -- this is non-graceful
+///
+/// Partially completed combinations are reported with their missing iterations before the
+/// execution starts, combinations without any runs are executed completely.
+fn completed_runs(
+    connection: &database::ConnectionAdapter,
+    config: &config::SolverConfig,
+) -> Result<database::CompletedRuns, database::ConnectionError> {
+    match connection.completed_runs() {
+        Ok(completed) => {
+            info!(
+                "Resuming benchmark, {} runs of {} combinations of test sets, solvers and instances were completed already",
+                completed.values().sum::<usize>(),
+                completed.len()
+            );
+
+            let mut missing = 0;
+
+            for ((test_set, solver, instance), count) in completed.iter() {
+                let Some(set) = config.tests.get(test_set) else {
+                    continue;
+                };
+
+                if *count < set.iterations {
+                    info!(
+                        test_set = %test_set,
+                        solver = %solver,
+                        instance = %instance,
+                        "Missing iterations {count}..{} of {test_set}/{solver} on {instance}",
+                        set.iterations
+                    );
+                    missing += set.iterations - count;
+                }
+            }
+
+            if missing > 0 {
+                info!("{missing} iterations of partially completed combinations are missing");
+            }
+
+            Ok(completed)
+        }
+        Err(error) => {
+            error!(error = ?error, "Failed to load completed runs: {error}");
+
+            Err(error)
+        }
+    }
+}
+
+/// Check the runs of a finished benchmark for conflicting answers, they are only reported
+fn check_conflicts(database: &config::DatabaseConfig, benchmark: database::ID) {
+    match database::ConnectionAdapter::load_connection(database).and_then(|connection| {
+        let conflicts = analysis::check::check_benchmark(&connection, benchmark)?;
+        connection.close()?;
+
+        Ok(conflicts)
+    }) {
+        Ok(conflicts) if conflicts.is_empty() => debug!("Found no conflicting answers"),
+        Ok(conflicts) => warn!(
+            "Found conflicting answers on {} instances, see `satan-runner check --benchmark {benchmark}`",
+            conflicts.len()
+        ),
+        // ClickHouse doesn't support checks
+        Err(database::ConnectionError::ConfigError) => (),
+        Err(error) => error!(error = ?error, "Failed to check for conflicting answers: {error}"),
+    }
+}
+
+/// Execute a benchmark suite with work coordinated over MPI
+///
+/// The coordinator rank collects all tasks and stores the results, all other ranks only execute
+/// solvers.
+#[cfg(feature = "mpi")]
+fn execute_coordinated(
+    mut config: config::SolverConfig,
+    benchmark: Option<i32>,
+    comment: Option<String>,
+    resume: bool,
+) -> Result<(), ConfigErrors> {
+    let universe = match mpi::initialize() {
+        Some(universe) => universe,
+        None => {
+            error!("Failed to initialize MPI, it may only be initialized once");
+
+            exit(1)
+        }
+    };
+    let coordinator = distributed::mpi::MPICoordinator::new(universe.world());
+
+    let result = if coordinator.is_coordinator() {
+        // workers wait for the coordinator until they are released, also if it fails to start
+        let abort = || -> ! {
+            coordinator.abort();
+
+            exit(1)
+        };
+
+        let mut connection = match database::ConnectionAdapter::load(&config.database) {
+            Ok(connection) => connection,
+            Err(error) => {
+                error!(error = ?error, "Failed to load connection: {error}");
+
+                abort()
+            }
+        };
+
+        if let Err(error) = connection.init(&config, benchmark, comment) {
+            error!(error = ?error, "Failed to initialize the database connection: {error}");
+
+            abort()
+        };
+
+        let completed = if resume {
+            completed_runs(&connection, &config).unwrap_or_else(|_| abort())
+        } else {
+            database::CompletedRuns::new()
+        };
+
+        let collectors = match config.collectors() {
+            Ok(mut collectors) => {
+                collectors.iter_mut().for_each(|(_, value)| {
+                    *value = Collector::mpi(value.clone());
+                });
+
+                collectors
+            }
+            Err((name, error)) => {
+                error!(error = ?error, name = %name, "Failed to compile collector for {name}: {error}");
+                abort()
+            }
+        };
+
+        // the runs are checked for conflicting answers once all of them are stored
+        let benchmark = connection.benchmark();
+
+        coordinator
+            .coordinate(connection, &config, collectors, completed)
+            .map(|()| Some(benchmark))
+    } else {
+        let ingestors = match config.load_ingestors() {
+            Ok(ingestors) => ingestors,
+            Err(error) => {
+                error!(
+                    error = ?error,
+                    "Preflight checks failed on ingestors: {error}"
+                );
+                exit(1)
+            }
+        };
+
+        coordinator.work(&config, &ingestors).map(|()| None)
+    };
+
+    match result {
+        Ok(benchmark) => {
+            info!("Finished execution");
+
+            // only the coordinator knows the database
+            if let Some(benchmark) = benchmark {
+                check_conflicts(&config.database, benchmark);
+            }
+        }
+        Err(error) => {
+            error!(error = ?error, "Executor failed: {error}");
+
+            exit(1)
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "distributed", not(feature = "mpi")))]
+fn execute_coordinated(
+    _config: config::SolverConfig,
+    _benchmark: Option<i32>,
+    _comment: Option<String>,
+    _resume: bool,
+) -> Result<(), ConfigErrors> {
+    error!("This binary was compiled without MPI support, enable the mpi feature for coordinated execution");
+
+    exit(1)
+}
@@ -0,0 +1,67 @@
+use signal_hook::{
+    consts::{SIGINT, SIGTERM},
+    iterator::Signals,
+};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// number of received SIGINT and SIGTERM signals
+static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+/// time of the first signal
+static REQUESTED: OnceLock<Instant> = OnceLock::new();
+/// time running solvers may continue after the first signal
+static GRACE_PERIOD: OnceLock<Duration> = OnceLock::new();
+
+/// Handle SIGINT and SIGTERM on a dedicated thread
+///
+/// The first signal requests a shutdown: no new runs are started and running solvers may finish
+/// within `grace_period`. Any further signal terminates running solvers immediately.
+pub fn install(grace_period: Duration) -> io::Result<()> {
+    let _ = GRACE_PERIOD.set(grace_period);
+    let mut signals = Signals::new([SIGINT, SIGTERM])?;
+
+    thread::Builder::new()
+        .name("signal-handler".to_owned())
+        .spawn(move || {
+            for signal in signals.forever() {
+                if SIGNALS.fetch_add(1, Ordering::SeqCst) == 0 {
+                    let _ = REQUESTED.set(Instant::now());
+
+                    warn!(
+                        signal = signal,
+                        "Shutting down, running solvers may finish within {} ms, send the signal again to kill them",
+                        grace_period.as_millis()
+                    );
+                } else {
+                    warn!(signal = signal, "Killing running solvers");
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+/// whether a shutdown was requested, no new runs should be started
+pub fn requested() -> bool {
+    SIGNALS.load(Ordering::SeqCst) > 0
+}
+
+/// whether running solvers have to be terminated, i.e., the grace period ran out or a second
+/// signal was received
+pub fn expired() -> bool {
+    match REQUESTED.get() {
+        Some(requested) => {
+            SIGNALS.load(Ordering::SeqCst) > 1
+                || requested.elapsed() >= GRACE_PERIOD.get().copied().unwrap_or_default()
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    env,
+    fs::{remove_dir_all, remove_file, write},
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// number of temporary paths handed out by this process
+static PATHS: AtomicUsize = AtomicUsize::new(0);
+
+/// Unique path in the temporary directory that is removed once dropped
+///
+/// The path is removed even if the test panics, files written next to it (e.g., SQLite journals)
+/// are left to the temporary directory.
+#[derive(Debug)]
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    /// new path ending with `name`, nothing is created yet
+    pub fn new(name: &str) -> Self {
+        Self(env::temp_dir().join(format!(
+            "satan-{}-{}-{name}",
+            process::id(),
+            PATHS.fetch_add(1, Ordering::Relaxed)
+        )))
+    }
+
+    /// new file ending with `name` containing `content`
+    pub fn with_content(name: &str, content: &[u8]) -> Self {
+        let path = Self::new(name);
+        write(&path, content).unwrap();
+
+        path
+    }
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // missing paths are fine, not every test creates its path
+        let _ = if self.0.is_dir() {
+            remove_dir_all(&self.0)
+        } else {
+            remove_file(&self.0)
+        };
+    }
+}